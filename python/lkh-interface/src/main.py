@@ -1,4 +1,5 @@
 import argparse
+import json
 
 from lkh import LKHProblem, solve
 from tsplib95.models import StandardProblem
@@ -10,14 +11,28 @@ parser = argparse.ArgumentParser(
 )
 
 
-parser.add_argument("tsplib_file")
+parser.add_argument("tsplib_file", nargs="?")
 parser.add_argument("--lkh-instance", default="../../bin/LKH")
 parser.add_argument("--output-file")
 parser.add_argument("-t", "--max-trials", default=1000)
 parser.add_argument("-r", "--runs", default=10)
+parser.add_argument(
+    "--request", help="external solver protocol request, replaces the other arguments"
+)
 
 args = parser.parse_args()
 
+request = None
+if args.request:
+    with open(args.request) as file:
+        request = json.load(file)
+    args.tsplib_file = request["instance"]
+    args.lkh_instance = request["options"].get("lkh_instance", args.lkh_instance)
+    args.max_trials = request["options"].get("max_trials", args.max_trials)
+    args.runs = request["options"].get("runs", args.runs)
+elif args.tsplib_file is None:
+    parser.error("the tsplib_file or a --request is required")
+
 
 problem = LKHProblem.load(args.tsplib_file)
 
@@ -28,6 +43,8 @@ if sum(problem.demands.values()) <= problem.capacity:
 
 if len(problem.node_coords.values()) > 2:
     extra = {}
+    if request is not None and request.get("time_limit") is not None:
+        extra["time_limit"] = request["time_limit"]
     tours = solve(
         args.lkh_instance, problem=problem, max_trials=args.max_trials, **extra
     )
@@ -46,3 +63,13 @@ else:
 
 if args.output_file is not None:
     tour.save(args.output_file)
+
+if request is not None:
+    with open(request["response_file"], "w") as out:
+        json.dump(
+            {
+                "tours": [[int(node) for node in path] for path in tour.tours],
+                "metadata": {"solver": "LKH-3", "max_trials": args.max_trials},
+            },
+            out,
+        )
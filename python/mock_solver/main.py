@@ -0,0 +1,62 @@
+"""Mock external solver speaking the protocol of EXTERNAL_SOLVERS.md.
+
+Uses only the standard library. Tour requests are answered with the nodes of the
+instance in id order starting at the depot, sample requests with random bitstrings.
+
+    python3 main.py [request.json]   # reads the request from stdin without a file
+"""
+
+import json
+import random
+import sys
+
+
+def read_nodes(instance):
+    nodes, depots, section = [], [], None
+    with open(instance) as file:
+        for line in file:
+            line = line.strip()
+            if not line or line == "EOF":
+                continue
+            if line[0].isalpha():
+                section = line.split()[0].rstrip(":")
+                continue
+            if section == "NODE_COORD_SECTION":
+                nodes.append(int(line.split()[0]))
+            elif section == "DEPOT_SECTION" and int(line.split()[0]) > 0:
+                depots.append(int(line.split()[0]))
+    depot = min(depots) if depots else min(nodes)
+    return [depot] + [node for node in sorted(nodes) if node != depot]
+
+
+def main():
+    if len(sys.argv) > 1:
+        with open(sys.argv[1]) as file:
+            request = json.load(file)
+    else:
+        request = json.load(sys.stdin)
+
+    options = request.get("options", {})
+    print(f"mock solver got a {request['kind']} request")
+
+    response = {"metadata": {"solver": "mock", "protocol": request["protocol"]}}
+    if request["kind"] == "tour":
+        response["tours"] = [read_nodes(request["instance"])]
+    elif request["kind"] == "samples":
+        rng = random.Random(options.get("seed"))
+        variables = request["qubo"]["variables"]
+        reads = int(options.get("reads", 10))
+        response["sample_set"] = {
+            "samples": [
+                [rng.randint(0, 1) for _ in range(variables)] for _ in range(reads)
+            ]
+        }
+    else:
+        response["error"] = f"unknown request kind {request['kind']}"
+
+    with open(request["response_file"], "w") as file:
+        json.dump(response, file)
+
+
+if __name__ == "__main__":
+    main()
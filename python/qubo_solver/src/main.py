@@ -1,4 +1,5 @@
 import argparse
+import json
 import os
 from datetime import datetime
 from typing import Literal
@@ -16,13 +17,32 @@ def main():
         epilog="Made by Lucas Berger for scientific purposes",
     )
 
-    parser.add_argument("file")
+    parser.add_argument("file", nargs="?")
     parser.add_argument(
-        "type", default="sim", choices=["sim", "hybrid", "qbsolv", "direct"]
+        "type", nargs="?", default="sim", choices=["sim", "hybrid", "qbsolv", "direct"]
     )
     parser.add_argument("--output-file")
+    parser.add_argument(
+        "--sample-set",
+        action="store_true",
+        help="write all samples with their energies and occurrences as json",
+    )
+    parser.add_argument(
+        "--request",
+        help="external solver protocol request, replaces the other arguments",
+    )
 
     args = parser.parse_args()
+    request = None
+    time_limit = None
+    if args.request:
+        with open(args.request) as file:
+            request = json.load(file)
+        args.file = request["qubo"]["files"]["lp"]
+        args.type = request["options"].get("type", "sim")
+        time_limit = request.get("time_limit")
+    elif args.file is None:
+        parser.error("the file or a --request is required")
     type: Literal["sim", "hybrid", "qbsolv", "direct"] = args.type
 
     bqm: BinaryQuadraticModel | None = None
@@ -58,7 +78,7 @@ def main():
 
     now = datetime.now().timestamp()
     print(f"connected after {now - last}. starting solver")
-    sampleset = solve_with(bqm, type, filename)
+    sampleset = solve_with(bqm, type, filename, time_limit)
 
     # accessing the sampleset's properties await for the future
     print(sampleset.info)
@@ -66,7 +86,27 @@ def main():
     now = datetime.now().timestamp()
     print(f"ended {now - last}")
 
-    if args.output_file:
+    if request is not None or (args.output_file and args.sample_set):
+        variables = sorted(sampleset.variables)
+        samples, energies, counts = [], [], []
+        for datum in sampleset.data(
+            ["sample", "energy", "num_occurrences"], sorted_by=None
+        ):
+            samples.append([int(datum.sample[v]) for v in variables])
+            energies.append(float(datum.energy))
+            counts.append(int(datum.num_occurrences))
+        sample_set = {"samples": samples, "energies": energies, "counts": counts}
+        if request is not None:
+            response = {
+                "sample_set": sample_set,
+                "metadata": {"type": type, "info": sampleset.info},
+            }
+            with open(request["response_file"], "w") as out:
+                json.dump(response, out, default=str)
+        else:
+            with open(args.output_file, "w") as out:
+                json.dump(sample_set, out)
+    elif args.output_file:
         with open(args.output_file, "w") as out:
             out.writelines([f"{bin}\n" for bin in sampleset.first.sample.values()])
     else:
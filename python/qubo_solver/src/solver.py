@@ -13,7 +13,12 @@ from hybrid.profiling import make_timeit
 solvertype = Literal["sim", "hybrid", "qbsolv", "direct"]
 
 
-def solve_with(bqm: BinaryQuadraticModel, type: solvertype, label: str) -> SampleSet:
+def solve_with(
+    bqm: BinaryQuadraticModel,
+    type: solvertype,
+    label: str,
+    time_limit: float | None = None,
+) -> SampleSet:
     if type == "sim":
         last = datetime.now().timestamp()
         sampler: Sampler = SimulatedAnnealingSampler()
@@ -62,12 +67,16 @@ def solve_with(bqm: BinaryQuadraticModel, type: solvertype, label: str) -> Sampl
         sampler: Sampler = LeapHybridSampler()
         print(f"sampler created took {datetime.now().timestamp() - last}")
         return sampler.sample(
-            bqm, time_limit=10, label=f"LeapHybridSampler num_reads=250: {label}"
+            bqm,
+            time_limit=time_limit if time_limit is not None else 10,
+            label=f"LeapHybridSampler num_reads=250: {label}",
         )
     elif type == "qbsolv":
         last = datetime.now().timestamp()
         init_state = State.from_problem(bqm)
-        workflow = SimplifiedQbsolv(max_iter=3, max_time=10)
+        workflow = SimplifiedQbsolv(
+            max_iter=3, max_time=time_limit if time_limit is not None else 10
+        )
         print(f"workflow created took {datetime.now().timestamp() - last}")
         final_state = workflow.run(init_state).result()
 
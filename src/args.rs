@@ -1,4 +1,10 @@
-use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap::{builder::RangedU64ValueParser, Args, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+
+use crate::logic::{
+    qubo::{MAX_ENUMERATED_VARIABLES, MAX_SIMULATED_QUBITS},
+    solvers::MAX_EXACT_NODES,
+};
 
 #[derive(Debug, Parser)]
 #[clap(author, version, about)]
@@ -10,32 +16,82 @@ pub struct VRPSolverArgs {
 #[derive(Debug, Subcommand)]
 pub enum VRPCommand {
     /// solve a vrp instance
-    Solve(SolveCommand),
+    Solve(Box<SolveCommand>),
     /// conduct part of the solving step
     Partial(PartialSolveCommand),
+    /// check the TSP QUBO of a small instance against brute force tour lengths
+    VerifyQubo(VerifyQuboCommand),
+    /// break down the QUBO energy of the samples in a sample file
+    QuboEnergy(QuboEnergyCommand),
 }
 
-#[derive(Debug, Args)]
+#[derive(Debug, Args, Serialize, Deserialize)]
 pub struct SolveCommand {
     /// CVRP-tsplib file path or a folder containing CVRP-tsplib instances
     pub path: String,
-    /// clustering config
-    #[arg(value_enum)]
-    pub cluster: ClusterOption,
-    #[arg(short = 'n', long, default_value_t = 3)]
-    pub cluster_number: usize,
-    #[arg(short = 'c', long, default_value_t = String::from("./.vrp"))]
-    pub cluster_file: String,
-    #[arg(short = 's', long, default_value_t = String::from("./.vrp"))]
-    pub solution_dir: String,
-    #[arg(value_enum)]
-    pub solver: SolverOption,
+    /// clustering config (ignored by the route-first pipeline)
+    #[arg(value_enum, required_unless_present = "config")]
+    pub cluster: Option<ClusterOption>,
+    /// decomposition order of the pipeline
+    #[arg(value_enum, long, default_value_t = PipelineOption::ClusterFirst)]
+    pub pipeline: PipelineOption,
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub clustering: ClusterArgs,
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub solving: SolverArgs,
+    /// TOML pipeline config, settings given on the command line override it
     #[arg(long)]
-    pub qubo_solution: Option<String>,
+    #[serde(skip)]
+    pub config: Option<String>,
+    /// keep clustering subproblems with more nodes, counting the depot
     #[arg(long)]
-    pub lkh_solution: Option<String>,
+    pub max_nodes: Option<usize>,
+    /// keep clustering subproblems whose QUBO has more variables
+    #[arg(long)]
+    pub qubit_budget: Option<usize>,
+    /// clustering of the subproblems per level, the last one is used for deeper levels
+    #[arg(value_enum, long, value_delimiter = ',', default_values_t = [ClusterOption::Tsp])]
+    pub level_clusters: Vec<ClusterOption>,
+    /// solver of the subproblems of the levels below the first per level, the last one is
+    /// used for deeper levels [default: the solver]
+    #[arg(value_enum, long, value_delimiter = ',')]
+    pub level_solvers: Vec<SolverOption>,
+    /// levels of subproblems that are clustered at most
+    #[arg(long, default_value_t = 4)]
+    pub max_depth: usize,
+    /// clusters of a level that are solved at the same time
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    pub workers: u64,
     #[arg(short = 'd', long, default_value_t = String::from("./.vrp"))]
     pub build_dir: String,
+    /// keep the subproblems in memory instead of writing them to the build dir
+    #[arg(long, default_value_t = false)]
+    pub in_memory: bool,
+}
+
+/// Parameters of the clustering strategies, shared by the solve and cluster commands.
+#[derive(Debug, Args, Serialize, Deserialize)]
+pub struct ClusterArgs {
+    #[arg(short = 'n', long, default_value_t = 3)]
+    pub cluster_number: usize,
+    #[arg(short = 'c', long, default_value_t = String::from("./.vrp"))]
+    pub cluster_file: String,
+    /// start angle of the sweep clustering in degrees
+    #[arg(long, default_value_t = 0f64)]
+    pub sweep_start_angle: f64,
+    #[arg(long, default_value_t = false)]
+    pub sweep_clockwise: bool,
+    /// number of evenly spaced start angles the sweep clustering tries
+    #[arg(long, default_value_t = 1)]
+    pub sweep_tries: usize,
+    /// maximum number of center updates of the capacitated k-means clustering
+    #[arg(long, default_value_t = 100)]
+    pub kmeans_iterations: usize,
+    /// time limit of the MILP solver of the generalized assignment clustering in seconds
+    #[arg(long, default_value_t = 60)]
+    pub assignment_time_limit: u32,
 }
 
 #[derive(Debug, Args)]
@@ -47,7 +103,7 @@ pub struct PartialSolveCommand {
 #[derive(Debug, Subcommand)]
 pub enum PartialSolveSubCommand {
     Cluster(OnlyClusterCommand),
-    Solve(OnlySolveCommand),
+    Solve(Box<OnlySolveCommand>),
 }
 
 #[derive(Debug, Args)]
@@ -57,40 +113,194 @@ pub struct OnlyClusterCommand {
     /// clustering config
     #[arg(value_enum)]
     pub cluster: ClusterOption,
-    #[arg(short = 'n', long, default_value_t = 3)]
-    pub cluster_number: usize,
-    #[arg(short = 'c', long, default_value_t = String::from("./.vrp"))]
-    pub cluster_file: String,
+    #[command(flatten)]
+    pub clustering: ClusterArgs,
     #[arg(short = 'd', long, default_value_t = String::from("./.vrp"))]
     pub build_dir: String,
 }
 
-#[derive(Debug, Args)]
-pub struct OnlySolveCommand {
-    /// CVRP-tsplib file path or a folder containing CVRP-tsplib instances
-    pub path: String,
-    #[arg(value_enum)]
-    pub solver: SolverOption,
-    #[arg(long, default_value_t = false)]
-    pub transform_only: bool,
+/// Solver of the subproblems and its parameters, shared by the solve commands.
+#[derive(Debug, Args, Serialize, Deserialize)]
+pub struct SolverArgs {
+    #[arg(value_enum, required_unless_present = "config")]
+    pub solver: Option<SolverOption>,
     #[arg(short = 's', long, default_value_t = String::from("./.vrp"))]
     pub solution_dir: String,
     #[arg(long)]
     pub qubo_solution: Option<String>,
+    /// variables of the TSP QUBO of the hybrid solvers
+    #[arg(value_enum, long, default_value_t = QuboFormulationOption::Full)]
+    pub qubo_formulation: QuboFormulationOption,
+    /// TOML file with the QUBO weights, the flags below override it, the
+    /// effective config records the resolved weights instead of the file
+    #[arg(long)]
+    pub qubo_config: Option<String>,
+    /// constraint penalty of the QUBO [default: size * objective weight * max distance]
+    #[arg(long)]
+    pub qubo_penalty: Option<f64>,
+    /// weight of the tour length in the QUBO [default: 1]
+    #[arg(long)]
+    pub qubo_objective_weight: Option<f64>,
+    /// raise the penalty from the longest edge until the sample is feasible
+    #[arg(long, default_value_t = false)]
+    pub qubo_auto_penalty: bool,
+    /// factor the penalty is raised by while tuning [default: 2]
+    #[arg(long)]
+    pub qubo_penalty_factor: Option<f64>,
+    /// number of reads of the native QUBO samplers
+    #[arg(long, default_value_t = 100)]
+    pub reads: usize,
+    /// sweeps over all variables per read of the native QUBO samplers
+    #[arg(long, default_value_t = 1000)]
+    pub sweeps: usize,
+    /// inverse temperature at the start of an anneal [default: derived from the QUBO]
+    #[arg(long, requires = "beta_end")]
+    pub beta_start: Option<f64>,
+    /// inverse temperature at the end of an anneal [default: derived from the QUBO]
+    #[arg(long, requires = "beta_start")]
+    pub beta_end: Option<f64>,
+    #[arg(value_enum, long, default_value_t = BetaScheduleOption::Geometric)]
+    pub beta_schedule: BetaScheduleOption,
+    /// seed of the native QUBO samplers
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// flips a variable stays tabu [default: min(20, variables / 4)]
+    #[arg(long)]
+    pub tabu_tenure: Option<usize>,
+    /// number of replicas of the parallel tempering sampler
+    #[arg(long, default_value_t = 8)]
+    pub replicas: usize,
+    /// number of cost and mixer layers of the QAOA sampler
+    #[arg(long, default_value_t = 2)]
+    pub qaoa_layers: usize,
+    /// maximum energy evaluations of the QAOA angle optimization
+    #[arg(long, default_value_t = 200)]
+    pub qaoa_iterations: usize,
+    /// largest QUBO the QAOA statevector is simulated for
+    #[arg(long, default_value_t = 16, value_parser = RangedU64ValueParser::<usize>::new().range(1..=MAX_SIMULATED_QUBITS as u64))]
+    pub qaoa_max_qubits: usize,
+    /// split QUBOs with more variables into sub-QUBOs of this size for the sampler
+    #[arg(long)]
+    pub max_qubo_variables: Option<usize>,
+    /// passes without improvement before the QUBO decomposition stops
+    #[arg(long, default_value_t = 5)]
+    pub decomposition_rounds: usize,
+    /// formats the QUBO of every subproblem is written in, comma separated
+    #[arg(value_enum, long, value_delimiter = ',', default_values_t = [QuboExportFormatOption::Coordinate, QuboExportFormatOption::DwaveLp])]
+    pub export_formats: Vec<QuboExportFormatOption>,
     #[arg(long)]
     pub lkh_solution: Option<String>,
+    /// name of the external solver of the external and external-qubo solvers
+    #[arg(long)]
+    pub external_solver: Option<String>,
+    /// TOML file configuring the external solvers [default: ./external_solvers.toml if present]
+    #[arg(long)]
+    pub external_config: Option<String>,
+    /// LKH-3 binary the lkh external solver runs
+    #[arg(long, default_value_t = String::from("./bin/LKH"))]
+    pub lkh_binary: String,
+    /// directory of the python wrappers of the built in external solvers
+    #[arg(long, default_value_t = String::from("./python"))]
+    pub python_dir: String,
+    /// maximum number of generations of the rust-vrp solver
+    #[arg(long)]
+    pub max_generations: Option<usize>,
+    /// time limit of the rust-vrp solver in seconds
+    #[arg(long)]
+    pub time_limit: Option<usize>,
+    #[arg(value_enum, long, default_value_t = InitialHeuristicOption::Default)]
+    pub initial_heuristic: InitialHeuristicOption,
+    /// print the telemetry of the rust-vrp solver
+    #[arg(long, default_value_t = false)]
+    pub telemetry: bool,
+    /// construction heuristic of the local-search solver
+    #[arg(value_enum, long, default_value_t = TourConstructionOption::NearestNeighbour)]
+    pub construction: TourConstructionOption,
+    /// size of the neighbour lists of the local-search solver
+    #[arg(long, default_value_t = 10)]
+    pub neighbours: usize,
+    /// largest subproblem the held-karp solver accepts
+    #[arg(long, default_value_t = 15, value_parser = RangedU64ValueParser::<usize>::new().range(2..=MAX_EXACT_NODES as u64))]
+    pub max_exact_nodes: usize,
+}
+
+#[derive(Debug, Args, Serialize, Deserialize)]
+pub struct OnlySolveCommand {
+    /// CVRP-tsplib file path or a folder containing CVRP-tsplib instances
+    pub path: String,
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub solving: SolverArgs,
+    #[arg(long, default_value_t = false)]
+    pub transform_only: bool,
+    /// TOML pipeline config, settings given on the command line override it
+    #[arg(long)]
+    #[serde(skip)]
+    pub config: Option<String>,
     #[arg(short = 'd', long, default_value_t = String::from("./.vrp"))]
     pub build_dir: String,
 }
 
-#[derive(Debug, Clone, ValueEnum)]
+#[derive(Debug, Args)]
+pub struct VerifyQuboCommand {
+    /// CVRP-tsplib file of a single TSP subproblem
+    pub path: String,
+    #[arg(value_enum, long, default_value_t = QuboFormulationOption::Full)]
+    pub qubo_formulation: QuboFormulationOption,
+    /// penalties to check, can be repeated [default: lower bound and default penalty]
+    #[arg(long)]
+    pub qubo_penalty: Vec<f64>,
+    /// weight of the tour length in the QUBO
+    #[arg(long, default_value_t = 1f64)]
+    pub qubo_objective_weight: f64,
+    /// largest QUBO whose ground states are enumerated
+    #[arg(long, default_value_t = 20, value_parser = RangedU64ValueParser::<usize>::new().range(1..=MAX_ENUMERATED_VARIABLES as u64))]
+    pub max_variables: usize,
+}
+
+#[derive(Debug, Args)]
+pub struct QuboEnergyCommand {
+    /// CVRP-tsplib file of a single TSP subproblem
+    pub path: String,
+    /// sample set or bitstring written by a QUBO solver
+    pub sample_file: String,
+    #[arg(value_enum, long, default_value_t = QuboFormulationOption::Full)]
+    pub qubo_formulation: QuboFormulationOption,
+    /// TOML file with the QUBO weights, the flags below override it
+    #[arg(long)]
+    pub qubo_config: Option<String>,
+    /// constraint penalty of the QUBO [default: size * objective weight * max distance]
+    #[arg(long)]
+    pub qubo_penalty: Option<f64>,
+    /// weight of the tour length in the QUBO [default: 1]
+    #[arg(long)]
+    pub qubo_objective_weight: Option<f64>,
+}
+
+#[derive(Debug, Clone, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ClusterOption {
     Kmeans,
+    CapacitatedKmeans,
+    Sweep,
+    GeneralizedAssignment,
+    /// solve the whole instance as a single cluster
+    None,
     Tsp,
     ClusterFromFile,
 }
 
-#[derive(Debug, Clone, ValueEnum)]
+#[derive(Debug, Clone, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PipelineOption {
+    /// cluster the customers and solve a TSP per cluster
+    ClusterFirst,
+    /// solve one giant TSP and split it into routes
+    RouteFirst,
+}
+
+#[derive(Debug, Clone, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum SolverOption {
     Lkh,
     Simulated,
@@ -98,4 +308,73 @@ pub enum SolverOption {
     QbSolv,
     Direct,
     SolutionFromFile,
+    RustVrp,
+    LocalSearch,
+    HeldKarp,
+    /// sample the QUBO with the native simulated annealer
+    Annealing,
+    /// sample the QUBO with native multistart tabu search
+    Tabu,
+    /// sample the QUBO with native parallel tempering
+    Tempering,
+    /// enumerate all assignments of the QUBO, only for tiny subproblems
+    Exhaustive,
+    /// native QAOA statevector simulation, up to --qaoa-max-qubits qubits
+    Qaoa,
+    /// solve the TSP with the external solver given by --external-solver
+    External,
+    /// sample the QUBO with the external solver given by --external-solver
+    ExternalQubo,
+}
+
+#[derive(Debug, Clone, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuboFormulationOption {
+    /// one variable per node and tour position
+    Full,
+    /// pin the depot to the first position and only encode the customers
+    DepotFixed,
+}
+
+#[derive(Debug, Clone, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuboExportFormatOption {
+    /// MatrixMarket coordinate matrix
+    Coordinate,
+    /// LP file of the python qubo solver
+    DwaveLp,
+    /// LP file with quadratic objective and binary section
+    Lp,
+    /// qbsolv .qubo file
+    Qbsolv,
+    /// dimod binary quadratic model JSON
+    BqmJson,
+    /// Ising fields, couplings and offset
+    Ising,
+    /// Pauli Z and ZZ strings for gate based QAOA, with a qubit manifest
+    Pauli,
+}
+
+#[derive(Debug, Clone, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BetaScheduleOption {
+    Linear,
+    Geometric,
+}
+
+#[derive(Debug, Clone, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TourConstructionOption {
+    NearestNeighbour,
+    Greedy,
+}
+
+#[derive(Debug, Clone, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InitialHeuristicOption {
+    Default,
+    Cheapest,
+    Farthest,
+    Regret,
+    NearestNeighbor,
 }
@@ -0,0 +1,113 @@
+use std::{fs, path::Path, process::exit};
+
+use clap::{parser::ValueSource, ArgMatches, Args, Command};
+use serde::{de::DeserializeOwned, Serialize};
+use toml::{Table, Value};
+
+/// Applies the TOML pipeline config to every setting not given on the command line.
+///
+/// Settings are named like the flags in snake or kebab case and may be grouped in
+/// tables like `[qubo]`, whose names are only for readability.
+pub fn apply_config<T: Args + Serialize + DeserializeOwned>(
+    args: T,
+    matches: &ArgMatches,
+    path: &str,
+) -> T {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            println!("Problem opening pipeline config {path}: {e}");
+            exit(1)
+        }
+    };
+    let config: Table = match toml::from_str(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("Problem parsing pipeline config {path}: {e}");
+            exit(1)
+        }
+    };
+
+    let known = T::augment_args(Command::new("config"))
+        .get_arguments()
+        .map(|arg| arg.get_id().to_string())
+        .filter(|id| id != "config")
+        .collect::<Vec<String>>();
+    let mut settings = Table::try_from(&args).unwrap();
+    let mut entries = vec![];
+    flatten(config, &known, &mut entries);
+    for (key, value) in entries {
+        if !known.contains(&key) {
+            println!("Unknown setting {key} in pipeline config {path}");
+            exit(1)
+        }
+        if matches.value_source(&key) != Some(ValueSource::CommandLine) {
+            settings.insert(key, value);
+        }
+    }
+
+    match settings.try_into() {
+        Ok(args) => args,
+        Err(e) => {
+            println!("Problem with the settings of pipeline config {path}: {e}");
+            exit(1)
+        }
+    }
+}
+
+/// Collects the settings of the config, descending into tables that aren't settings.
+fn flatten(table: Table, known: &[String], entries: &mut Vec<(String, Value)>) {
+    for (key, value) in table {
+        let key = key.replace('-', "_");
+        match value {
+            Value::Table(section) if !known.contains(&key) => flatten(section, known, entries),
+            value => entries.push((key, value)),
+        }
+    }
+}
+
+/// Setting that has to be given on the command line or in the pipeline config.
+pub fn required<'a, T>(value: &'a Option<T>, name: &str) -> &'a T {
+    match value {
+        Some(value) => value,
+        None => {
+            println!("No {name} given, neither on the command line nor in the pipeline config");
+            exit(1)
+        }
+    }
+}
+
+/// Writes the effective settings of a run to `path`, where they can be passed to
+/// `--config` to repeat it.
+pub fn write_effective_config<T: Serialize>(args: &T, path: &str) {
+    let content = match toml::to_string(args) {
+        Ok(content) => content,
+        Err(e) => {
+            println!("Problem serializing the pipeline config: {e}");
+            exit(1)
+        }
+    };
+    if let Some(dir) = Path::new(path).parent() {
+        if let Err(e) = fs::create_dir_all(dir) {
+            println!("Something went wrong creating dirs: \n{e}");
+            exit(1)
+        }
+    }
+    match fs::write(path, content) {
+        Ok(_) => println!("writing effective config to file {path}"),
+        Err(e) => {
+            println!("Problem writing pipeline config {path}: {e}");
+            exit(1)
+        }
+    }
+}
+
+/// Config file of the instance or folder at `path` in the solution dir `dir`.
+pub fn effective_config_path(dir: &str, path: &str) -> String {
+    let path = Path::new(path);
+    if path.is_dir() {
+        return format!("{dir}/pipeline.config.toml");
+    }
+    let file_name = path.file_stem().unwrap().to_str().unwrap();
+    format!("{dir}/{file_name}.config.toml")
+}
@@ -0,0 +1,183 @@
+use super::common::{distance, ClusterOutput, ClusteringTrait};
+use tspf::{Point, Tsp};
+
+/// K-means clustering where every cluster has to fit into a single vehicle.
+///
+/// The number of clusters starts at `ceil(total demand / capacity)` and is
+/// increased whenever the customers can't be packed into the clusters.
+pub struct CapacitatedKMeansClustering {
+    pub max_iterations: usize,
+}
+
+impl CapacitatedKMeansClustering {
+    fn demand(&self, problem: &Tsp, id: usize) -> f64 {
+        *problem.demands().get(&id).unwrap_or(&0f64)
+    }
+
+    /// Farthest point initialization starting with the customer farthest away from the depots.
+    fn initial_centers(&self, problem: &Tsp, points: &[&Point], count: usize) -> Vec<Vec<f64>> {
+        let depots = problem
+            .depots()
+            .iter()
+            .filter_map(|id| problem.node_coords().get(id))
+            .map(|p| p.pos().clone())
+            .collect::<Vec<Vec<f64>>>();
+
+        let mut centers: Vec<Vec<f64>> = vec![];
+        while centers.len() < count {
+            let references = if centers.is_empty() {
+                &depots
+            } else {
+                &centers
+            };
+
+            let farthest = points
+                .iter()
+                .map(|p| {
+                    let closest = references
+                        .iter()
+                        .map(|c| distance(p.pos(), c).unwrap())
+                        .fold(f64::INFINITY, f64::min);
+                    (p, closest)
+                })
+                .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+            match farthest {
+                Some((point, _)) => centers.push(point.pos().clone()),
+                None => break,
+            }
+        }
+        centers
+    }
+
+    /// Capacity respecting Lloyd assignment step.
+    ///
+    /// Customers are assigned in order of their regret, i.e. how much worse their second
+    /// best center is compared to the best one. Returns `None` if some customer doesn't
+    /// fit into any cluster.
+    fn assign(&self, problem: &Tsp, points: &[&Point], centers: &[Vec<f64>]) -> Option<Vec<usize>> {
+        let mut preferences = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let mut order = centers
+                    .iter()
+                    .enumerate()
+                    .map(|(c, center)| (c, distance(p.pos(), center).unwrap()))
+                    .collect::<Vec<(usize, f64)>>();
+                order.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+                let regret = if order.len() > 1 {
+                    order[1].1 - order[0].1
+                } else {
+                    0f64
+                };
+                (i, order, regret)
+            })
+            .collect::<Vec<(usize, Vec<(usize, f64)>, f64)>>();
+
+        preferences.sort_by(|(_, _, a), (_, _, b)| b.total_cmp(a));
+
+        let mut loads = vec![0f64; centers.len()];
+        let mut assignments = vec![0usize; points.len()];
+
+        for (i, order, _) in preferences {
+            let demand = self.demand(problem, points[i].id());
+            let (cluster, _) = order
+                .iter()
+                .find(|(c, _)| loads[*c] + demand <= problem.capacity())?;
+            loads[*cluster] += demand;
+            assignments[i] = *cluster;
+        }
+
+        Some(assignments)
+    }
+
+    fn update_centers(
+        &self,
+        points: &[&Point],
+        assignments: &[usize],
+        centers: &[Vec<f64>],
+    ) -> Vec<Vec<f64>> {
+        centers
+            .iter()
+            .enumerate()
+            .map(|(c, center)| {
+                let members = points
+                    .iter()
+                    .zip(assignments.iter())
+                    .filter(|(_, a)| **a == c)
+                    .map(|(p, _)| p.pos())
+                    .collect::<Vec<&Vec<f64>>>();
+                if members.is_empty() {
+                    return center.clone();
+                }
+                (0..center.len())
+                    .map(|d| members.iter().map(|pos| pos[d]).sum::<f64>() / members.len() as f64)
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl ClusteringTrait for CapacitatedKMeansClustering {
+    fn cluster(&self, problem: &Tsp) -> ClusterOutput {
+        let mut points = problem
+            .node_coords()
+            .values()
+            .filter(|p| !problem.depots().contains(&p.id()))
+            .collect::<Vec<&Point>>();
+        points.sort_by_key(|p| p.id());
+
+        if let Some(p) = points
+            .iter()
+            .find(|p| self.demand(problem, p.id()) > problem.capacity())
+        {
+            println!(
+                "customer {} has a demand greater than the vehicle capacity",
+                p.id()
+            );
+            std::process::exit(1);
+        }
+
+        let total_demand: f64 = points.iter().map(|p| self.demand(problem, p.id())).sum();
+        let mut count = ((total_demand / problem.capacity()).ceil() as usize).max(1);
+
+        let (centers, assignments) = loop {
+            let mut centers = self.initial_centers(problem, &points, count);
+            let mut assignments = match self.assign(problem, &points, &centers) {
+                Some(assignments) => assignments,
+                None => {
+                    count += 1;
+                    continue;
+                }
+            };
+
+            for _ in 0..self.max_iterations {
+                let new_centers = self.update_centers(&points, &assignments, &centers);
+                let new_assignments = match self.assign(problem, &points, &new_centers) {
+                    Some(assignments) => assignments,
+                    // keep the last feasible assignment
+                    None => break,
+                };
+                centers = new_centers;
+                if new_assignments == assignments {
+                    break;
+                }
+                assignments = new_assignments;
+            }
+
+            break (centers, assignments);
+        };
+
+        assignments
+            .iter()
+            .enumerate()
+            .fold(vec![vec![]; centers.len()], |mut x, (i, y)| {
+                x[*y].push(points[i].id());
+                x
+            })
+            .into_iter()
+            .filter(|cluster: &Vec<usize>| !cluster.is_empty())
+            .collect()
+    }
+}
@@ -1,22 +1,8 @@
-use super::common::{ClusterOutput, ClusteringTrait};
+use super::common::{distance, ClusterOutput, ClusteringTrait};
 use tspf::{Point, Tsp};
 
 pub struct ClusterTspClustering {}
 
-fn distance(a: &Vec<f64>, b: &Vec<f64>) -> Option<f64> {
-    if a.len() != b.len() {
-        return None;
-    }
-    Some(
-        a.iter()
-            .zip(b.iter())
-            .map(|(av, bv)| (av - bv) * (av - bv))
-            .reduce(|a, b| a + b)
-            .unwrap()
-            .sqrt(),
-    )
-}
-
 impl ClusterTspClustering {
     fn choose_core<'a>(&'a self, _problem: &Tsp, rest: &'a [usize]) -> Option<&usize> {
         rest.first()
@@ -1,7 +1,30 @@
+use std::sync::Arc;
+
 use tspf::Tsp;
 
 pub type ClusterOutput = Vec<Vec<usize>>;
 
-pub trait ClusteringTrait {
+pub trait ClusteringTrait: Send + Sync {
     fn cluster(&self, problem: &Tsp) -> ClusterOutput;
 }
+
+impl<T: ClusteringTrait + ?Sized> ClusteringTrait for Arc<T> {
+    fn cluster(&self, problem: &Tsp) -> ClusterOutput {
+        (**self).cluster(problem)
+    }
+}
+
+/// Euclidean distance between two positions of equal dimension.
+pub fn distance(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() != b.len() {
+        return None;
+    }
+    Some(
+        a.iter()
+            .zip(b.iter())
+            .map(|(av, bv)| (av - bv) * (av - bv))
+            .reduce(|a, b| a + b)
+            .unwrap()
+            .sqrt(),
+    )
+}
@@ -0,0 +1,264 @@
+use std::cmp::Ordering;
+
+use super::common::{distance, ClusterOutput, ClusteringTrait};
+use lp_solvers::{
+    lp_format::{Constraint, LpObjective},
+    problem::{Problem, StrExpression, Variable},
+    solvers::{AutoSolver, CbcSolver, GlpkSolver, NoSolver, SolverTrait, Status, WithMaxSeconds},
+};
+use tspf::{Point, Tsp};
+
+/// Fisher–Jaikumar clustering.
+///
+/// Chooses one seed customer per vehicle and assigns every customer to a seed by solving
+/// a generalized assignment problem with the cost of inserting the customer into the
+/// route depot -> seed -> depot. Falls back to a greedy assignment if neither CBC nor
+/// GLPK is installed.
+pub struct GeneralizedAssignmentClustering {
+    /// Time limit for the MILP solver in seconds.
+    pub max_seconds: u32,
+}
+
+enum Assignment {
+    Feasible(Vec<usize>),
+    Infeasible,
+    NoSolver(String),
+}
+
+impl GeneralizedAssignmentClustering {
+    fn demand(&self, problem: &Tsp, id: usize) -> f64 {
+        *problem.demands().get(&id).unwrap_or(&0f64)
+    }
+
+    /// Picks the customers that are farthest away from the depot and all previous seeds.
+    fn seeds(&self, depot: &[f64], points: &[&Point], count: usize) -> Vec<usize> {
+        let mut seeds: Vec<usize> = vec![];
+        while seeds.len() < count.min(points.len()) {
+            let (seed, _) = points
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !seeds.contains(i))
+                .map(|(i, p)| {
+                    let closest = seeds
+                        .iter()
+                        .map(|s| distance(p.pos(), points[*s].pos()).unwrap())
+                        .fold(distance(p.pos(), depot).unwrap(), f64::min);
+                    (i, closest)
+                })
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .unwrap();
+            seeds.push(seed);
+        }
+        seeds
+    }
+
+    /// Insertion cost of every customer into the route depot -> seed -> depot.
+    fn costs(&self, depot: &[f64], points: &[&Point], seeds: &[usize]) -> Vec<Vec<f64>> {
+        points
+            .iter()
+            .map(|p| {
+                seeds
+                    .iter()
+                    .map(|s| {
+                        let seed = points[*s].pos();
+                        distance(depot, p.pos()).unwrap() + distance(p.pos(), seed).unwrap()
+                            - distance(depot, seed).unwrap()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn solve_milp(&self, problem: &Tsp, points: &[&Point], costs: &[Vec<f64>]) -> Assignment {
+        let seed_count = costs.first().map(|c| c.len()).unwrap_or(0);
+        let name = |i: usize, k: usize| format!("x_{}_{}", i, k);
+
+        let objective = costs
+            .iter()
+            .enumerate()
+            .flat_map(|(i, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(move |(k, cost)| format!("{} {}", cost, name(i, k)))
+            })
+            .collect::<Vec<String>>()
+            .join(" + ")
+            .replace("+ -", "- ");
+
+        let mut constraints = vec![];
+        for i in 0..points.len() {
+            constraints.push(Constraint {
+                lhs: StrExpression(
+                    (0..seed_count)
+                        .map(|k| name(i, k))
+                        .collect::<Vec<String>>()
+                        .join(" + "),
+                ),
+                operator: Ordering::Equal,
+                rhs: 1f64,
+            });
+        }
+        for k in 0..seed_count {
+            constraints.push(Constraint {
+                lhs: StrExpression(
+                    points
+                        .iter()
+                        .enumerate()
+                        .map(|(i, p)| format!("{} {}", self.demand(problem, p.id()), name(i, k)))
+                        .collect::<Vec<String>>()
+                        .join(" + "),
+                ),
+                operator: Ordering::Less,
+                rhs: problem.capacity(),
+            });
+        }
+
+        let lp = Problem {
+            name: "generalized_assignment".to_string(),
+            sense: LpObjective::Minimize,
+            objective: StrExpression(objective),
+            variables: (0..points.len())
+                .flat_map(|i| {
+                    (0..seed_count).map(move |k| Variable {
+                        name: name(i, k),
+                        is_integer: true,
+                        lower_bound: 0.,
+                        upper_bound: 1.,
+                    })
+                })
+                .collect(),
+            constraints,
+        };
+
+        // tries a locally installed CBC first and GLPK second
+        let solver = AutoSolver::<NoSolver, NoSolver>::new()
+            .with_solver(GlpkSolver::new().with_max_seconds(self.max_seconds))
+            .with_solver(CbcSolver::new().with_max_seconds(self.max_seconds));
+        let solution = match solver.run(&lp) {
+            Ok(solution) => solution,
+            Err(e) => return Assignment::NoSolver(e),
+        };
+
+        match solution.status {
+            Status::Optimal | Status::SubOptimal => {
+                let value = |i, k| *solution.results.get(&name(i, k)).unwrap_or(&0f32);
+                Assignment::Feasible(
+                    (0..points.len())
+                        .map(|i| {
+                            (0..seed_count)
+                                .max_by(|a, b| value(i, *a).total_cmp(&value(i, *b)))
+                                .unwrap()
+                        })
+                        .collect(),
+                )
+            }
+            _ => Assignment::Infeasible,
+        }
+    }
+
+    /// Regret based greedy assignment used without a MILP solver.
+    fn solve_greedy(&self, problem: &Tsp, points: &[&Point], costs: &[Vec<f64>]) -> Assignment {
+        let mut order = costs
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let mut sorted = row.iter().enumerate().collect::<Vec<(usize, &f64)>>();
+                sorted.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+                let regret = if sorted.len() > 1 {
+                    sorted[1].1 - sorted[0].1
+                } else {
+                    0f64
+                };
+                (i, sorted, regret)
+            })
+            .collect::<Vec<(usize, Vec<(usize, &f64)>, f64)>>();
+        order.sort_by(|(_, _, a), (_, _, b)| b.total_cmp(a));
+
+        let mut loads = vec![0f64; costs.first().map(|c| c.len()).unwrap_or(0)];
+        let mut assignments = vec![0usize; points.len()];
+        for (i, sorted, _) in order {
+            let demand = self.demand(problem, points[i].id());
+            match sorted
+                .iter()
+                .find(|(k, _)| loads[*k] + demand <= problem.capacity())
+            {
+                Some((k, _)) => {
+                    loads[*k] += demand;
+                    assignments[i] = *k;
+                }
+                None => return Assignment::Infeasible,
+            }
+        }
+        Assignment::Feasible(assignments)
+    }
+}
+
+impl ClusteringTrait for GeneralizedAssignmentClustering {
+    fn cluster(&self, problem: &Tsp) -> ClusterOutput {
+        let depot = match problem
+            .depots()
+            .iter()
+            .min()
+            .and_then(|id| problem.node_coords().get(id))
+        {
+            Some(point) => point.pos().clone(),
+            None => {
+                println!("Generalized assignment clustering needs node coordinates for the depot");
+                std::process::exit(1);
+            }
+        };
+
+        let mut points = problem
+            .node_coords()
+            .values()
+            .filter(|p| !problem.depots().contains(&p.id()))
+            .collect::<Vec<&Point>>();
+        points.sort_by_key(|p| p.id());
+
+        if let Some(p) = points
+            .iter()
+            .find(|p| self.demand(problem, p.id()) > problem.capacity())
+        {
+            println!(
+                "customer {} has a demand greater than the vehicle capacity",
+                p.id()
+            );
+            std::process::exit(1);
+        }
+
+        let total_demand: f64 = points.iter().map(|p| self.demand(problem, p.id())).sum();
+        let mut count = ((total_demand / problem.capacity()).ceil() as usize).max(1);
+        let mut use_milp = true;
+
+        let (seeds, assignments) = loop {
+            let seeds = self.seeds(&depot, &points, count);
+            let costs = self.costs(&depot, &points, &seeds);
+
+            let assignment = if use_milp {
+                self.solve_milp(problem, &points, &costs)
+            } else {
+                self.solve_greedy(problem, &points, &costs)
+            };
+
+            match assignment {
+                Assignment::Feasible(assignments) => break (seeds, assignments),
+                Assignment::Infeasible => count += 1,
+                Assignment::NoSolver(e) => {
+                    println!("no MILP solver found, falling back to greedy assignment: {e}");
+                    use_milp = false;
+                }
+            }
+        };
+
+        assignments
+            .iter()
+            .enumerate()
+            .fold(vec![vec![]; seeds.len()], |mut x, (i, k)| {
+                x[*k].push(points[i].id());
+                x
+            })
+            .into_iter()
+            .filter(|cluster: &Vec<usize>| !cluster.is_empty())
+            .collect()
+    }
+}
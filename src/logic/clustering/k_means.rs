@@ -16,9 +16,8 @@ impl ClusteringTrait for KMeansClustering {
 
         let coords = points
             .iter()
-            .map(|p| p.pos())
-            .flatten()
-            .map(|v| *v)
+            .flat_map(|p| p.pos())
+            .copied()
             .collect::<Vec<f64>>();
 
         let kmean = KMeans::new(coords, problem.dim() - problem.depots().len(), 2);
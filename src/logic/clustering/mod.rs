@@ -1,9 +1,17 @@
+mod capacitated_k_means;
 mod cluster_tsp;
 mod common;
 mod file_cluster;
+mod generalized_assignment;
 mod k_means;
+mod no_cluster;
+mod sweep;
 
+pub use capacitated_k_means::*;
 pub use cluster_tsp::*;
 pub use common::*;
 pub use file_cluster::*;
+pub use generalized_assignment::*;
 pub use k_means::*;
+pub use no_cluster::*;
+pub use sweep::*;
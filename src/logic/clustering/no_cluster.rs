@@ -0,0 +1,18 @@
+use super::common::{ClusterOutput, ClusteringTrait};
+use tspf::Tsp;
+
+/// Puts every customer into a single cluster, so the solver gets the whole problem.
+pub struct NoClustering {}
+
+impl ClusteringTrait for NoClustering {
+    fn cluster(&self, problem: &Tsp) -> ClusterOutput {
+        let mut customers = problem
+            .node_coords()
+            .keys()
+            .filter(|id| !problem.depots().contains(id))
+            .copied()
+            .collect::<Vec<usize>>();
+        customers.sort();
+        vec![customers]
+    }
+}
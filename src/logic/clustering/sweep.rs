@@ -0,0 +1,114 @@
+use std::f64::consts::PI;
+
+use super::common::{distance, ClusterOutput, ClusteringTrait};
+use tspf::Tsp;
+
+/// Classic sweep clustering.
+///
+/// Customers are ordered by their polar angle around the depot and a new cluster
+/// is started whenever the next customer would exceed the vehicle capacity.
+pub struct SweepClustering {
+    /// Angle in degrees at which the first sweep starts.
+    pub start_angle: f64,
+    pub clockwise: bool,
+    /// Number of evenly spaced start angles to try. The cheapest result is kept.
+    pub tries: usize,
+}
+
+impl SweepClustering {
+    fn sweep(&self, problem: &Tsp, customers: &[(usize, f64)], start: f64) -> ClusterOutput {
+        let mut ordered = customers
+            .iter()
+            .map(|(id, angle)| {
+                let relative = if self.clockwise {
+                    start - angle
+                } else {
+                    angle - start
+                };
+                (*id, relative.rem_euclid(2f64 * PI))
+            })
+            .collect::<Vec<(usize, f64)>>();
+        ordered.sort_by(|(a_id, a), (b_id, b)| a.total_cmp(b).then(a_id.cmp(b_id)));
+
+        let mut clusters: ClusterOutput = vec![];
+        let mut cluster = vec![];
+        let mut load = 0f64;
+        for (id, _) in ordered {
+            let demand = *problem.demands().get(&id).unwrap_or(&0f64);
+            if load + demand > problem.capacity() && !cluster.is_empty() {
+                clusters.push(cluster);
+                cluster = vec![];
+                load = 0f64;
+            }
+            cluster.push(id);
+            load += demand;
+        }
+        if !cluster.is_empty() {
+            clusters.push(cluster);
+        }
+        clusters
+    }
+
+    /// Length of the routes visiting every cluster in sweep order, starting and ending at the depot.
+    fn cost(&self, problem: &Tsp, depot: &[f64], clusters: &ClusterOutput) -> f64 {
+        clusters
+            .iter()
+            .map(|cluster| {
+                let mut length = 0f64;
+                let mut last = depot;
+                for id in cluster {
+                    let pos = problem.node_coords().get(id).unwrap().pos();
+                    length += distance(last, pos).unwrap();
+                    last = pos;
+                }
+                length + distance(last, depot).unwrap()
+            })
+            .sum()
+    }
+}
+
+impl ClusteringTrait for SweepClustering {
+    fn cluster(&self, problem: &Tsp) -> ClusterOutput {
+        let depot = match problem
+            .depots()
+            .iter()
+            .min()
+            .and_then(|id| problem.node_coords().get(id))
+        {
+            Some(point) => point.pos().clone(),
+            None => {
+                println!("Sweep clustering needs node coordinates for the depot");
+                std::process::exit(1);
+            }
+        };
+
+        let customers = problem
+            .node_coords()
+            .values()
+            .filter(|p| !problem.depots().contains(&p.id()))
+            .map(|p| {
+                let angle = (p.pos()[1] - depot[1]).atan2(p.pos()[0] - depot[0]);
+                (p.id(), angle)
+            })
+            .collect::<Vec<(usize, f64)>>();
+
+        if let Some((id, _)) = customers
+            .iter()
+            .find(|(id, _)| *problem.demands().get(id).unwrap_or(&0f64) > problem.capacity())
+        {
+            println!("customer {id} has a demand greater than the vehicle capacity");
+            std::process::exit(1);
+        }
+
+        let tries = self.tries.max(1);
+        let start = self.start_angle.to_radians();
+
+        (0..tries)
+            .map(|i| start + 2f64 * PI * (i as f64) / (tries as f64))
+            .map(|angle| self.sweep(problem, &customers, angle))
+            .map(|clusters| (self.cost(problem, &depot, &clusters), clusters))
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, clusters)| clusters)
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use super::{
+    clustering::ClusteringTrait,
+    solver::VrpSolver,
+    solvers::{Problem, SolvingOutput, SolvingTrait},
+};
+
+/// Clusters subproblems until they have at most `max_nodes` nodes and solves them
+/// with the leaf solvers.
+///
+/// Level `d` is clustered with `levels[d]` and its subproblems are solved with
+/// `leaf_solvers[d]`, deeper levels reuse the last strategy and solver.
+/// The subproblems of every level are written to the build dir like the ones of
+/// [`VrpSolver`], if there is one, and the tours are mapped back through its reindex maps.
+pub struct HierarchicalSolver {
+    pub levels: Vec<Arc<dyn ClusteringTrait>>,
+    pub leaf_solvers: Vec<Arc<dyn SolvingTrait>>,
+    /// Largest subproblem a leaf solver gets, counting the depot.
+    pub max_nodes: usize,
+    /// Number of levels after which subproblems go to a leaf solver regardless of size.
+    pub max_depth: usize,
+    /// Level whose subproblems all go to a leaf solver whatever their size, without the
+    /// warning of exceeding `max_depth`.
+    pub leaf_depth: Option<usize>,
+    pub build_dir: Option<String>,
+    /// Number of clusters of a level solved at the same time.
+    pub workers: usize,
+}
+
+impl HierarchicalSolver {
+    /// Solver of the subproblems of level `depth`.
+    fn level(&self, depth: usize) -> Box<dyn SolvingTrait> {
+        Box::new(SubproblemSolver {
+            solver: HierarchicalSolver {
+                levels: self.levels.clone(),
+                leaf_solvers: self.leaf_solvers.clone(),
+                max_nodes: self.max_nodes,
+                max_depth: self.max_depth,
+                leaf_depth: self.leaf_depth,
+                build_dir: self.build_dir.clone(),
+                workers: self.workers,
+            },
+            depth,
+        })
+    }
+
+    fn cluster(
+        &self,
+        depth: usize,
+        problem: &Problem,
+        transform_only: Option<bool>,
+    ) -> SolvingOutput {
+        let level = depth.min(self.levels.len() - 1);
+        VrpSolver {
+            cluster_strat: Box::new(self.levels[level].clone()),
+            solving_strat: self.level(depth + 1),
+            build_dir: self.build_dir.clone(),
+            workers: self.workers,
+        }
+        .solve(problem, transform_only)
+    }
+
+    /// Solver of the subproblems of level `depth` that aren't clustered further.
+    fn leaf_solver(&self, depth: usize) -> &dyn SolvingTrait {
+        let level = (depth - 1).min(self.leaf_solvers.len() - 1);
+        self.leaf_solvers[level].as_ref()
+    }
+}
+
+impl SolvingTrait for HierarchicalSolver {
+    fn solve(&self, problem: &Problem, transform_only: Option<bool>) -> SolvingOutput {
+        self.cluster(0, problem, transform_only)
+    }
+}
+
+/// A subproblem of the decomposition at level `depth`.
+struct SubproblemSolver {
+    solver: HierarchicalSolver,
+    depth: usize,
+}
+
+impl SolvingTrait for SubproblemSolver {
+    fn solve(&self, problem: &Problem, transform_only: Option<bool>) -> SolvingOutput {
+        let tsp = &problem.tsp;
+        let path = problem.label();
+
+        if tsp.dim() <= self.solver.max_nodes || self.solver.leaf_depth == Some(self.depth) {
+            return self
+                .solver
+                .leaf_solver(self.depth)
+                .solve(problem, transform_only);
+        }
+        if self.depth >= self.solver.max_depth {
+            println!(
+                "subproblem {path} still has {} nodes after {} levels, solving it anyway",
+                tsp.dim(),
+                self.depth
+            );
+            return self
+                .solver
+                .leaf_solver(self.depth)
+                .solve(problem, transform_only);
+        }
+
+        println!(
+            "subproblem {path} has {} nodes, clustering it on level {}",
+            tsp.dim(),
+            self.depth
+        );
+        self.solver.cluster(self.depth, problem, transform_only)
+    }
+}
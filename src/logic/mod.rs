@@ -1,4 +1,7 @@
 pub mod clustering;
+pub mod hierarchical;
+pub mod qubo;
+pub mod route_first;
 pub mod solver;
 pub mod solvers;
 pub mod util;
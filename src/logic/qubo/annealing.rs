@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{COOrdinate, QuboSampler, SampleSet};
+
+/// How the inverse temperature moves from `beta_start` to `beta_end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BetaSchedule {
+    Linear,
+    Geometric,
+}
+
+/// In-process Metropolis simulated annealing over a QUBO.
+pub struct SimulatedAnnealingSampler {
+    /// Number of independent anneals, each returns its final state.
+    pub reads: usize,
+    /// Sweeps over all variables per anneal.
+    pub sweeps: usize,
+    /// Inverse temperatures at the start and the end of an anneal, derived from
+    /// the QUBO coefficients if not set.
+    pub beta_range: Option<(f64, f64)>,
+    pub schedule: BetaSchedule,
+    pub seed: Option<u64>,
+}
+
+/// Linear terms and off-diagonal neighbours of every variable.
+pub(crate) struct Adjacency {
+    pub linear: Vec<f64>,
+    pub neighbours: Vec<Vec<(usize, f64)>>,
+}
+
+impl From<&COOrdinate> for Adjacency {
+    fn from(coo: &COOrdinate) -> Self {
+        let mut linear = vec![0f64; coo.variables()];
+        let mut neighbours = vec![vec![]; coo.variables()];
+        for (i, j, value) in coo.iter() {
+            if i == j {
+                linear[i] += value;
+            } else {
+                neighbours[i].push((j, value));
+                neighbours[j].push((i, value));
+            }
+        }
+        Adjacency { linear, neighbours }
+    }
+}
+
+impl Adjacency {
+    /// Energy change of every variable if it were flipped to one, given the others.
+    pub fn fields(&self, state: &[u8]) -> Vec<f64> {
+        (0..self.linear.len())
+            .map(|i| {
+                self.linear[i]
+                    + self.neighbours[i]
+                        .iter()
+                        .filter(|(j, _)| state[*j] == 1)
+                        .map(|(_, value)| value)
+                        .sum::<f64>()
+            })
+            .collect()
+    }
+
+    /// Energy change of flipping variable `i`.
+    pub fn delta(&self, state: &[u8], fields: &[f64], i: usize) -> f64 {
+        if state[i] == 1 {
+            -fields[i]
+        } else {
+            fields[i]
+        }
+    }
+
+    /// Flips variable `i` and updates the fields of its neighbours.
+    pub fn flip(&self, state: &mut [u8], fields: &mut [f64], i: usize) {
+        let sign = if state[i] == 1 { -1f64 } else { 1f64 };
+        state[i] = 1 - state[i];
+        for (j, value) in &self.neighbours[i] {
+            fields[*j] += sign * value;
+        }
+    }
+
+    /// One Metropolis sweep over all variables, returns the change of the energy.
+    pub fn metropolis_sweep(
+        &self,
+        state: &mut [u8],
+        fields: &mut [f64],
+        beta: f64,
+        rng: &mut StdRng,
+    ) -> f64 {
+        let mut change = 0f64;
+        for i in 0..state.len() {
+            let delta = self.delta(state, fields, i);
+            if delta <= 0f64 || rng.gen::<f64>() < (-beta * delta).exp() {
+                self.flip(state, fields, i);
+                change += delta;
+            }
+        }
+        change
+    }
+
+    /// Hot and cold inverse temperatures: at the start even the largest possible
+    /// energy change is accepted with probability 1/2, at the end the smallest
+    /// coefficient is accepted with probability 1/100.
+    pub fn default_beta_range(&self) -> (f64, f64) {
+        let mut max_delta = 0f64;
+        let mut min_coefficient = f64::INFINITY;
+        for i in 0..self.linear.len() {
+            let delta = self.linear[i].abs()
+                + self.neighbours[i]
+                    .iter()
+                    .map(|(_, value)| value.abs())
+                    .sum::<f64>();
+            max_delta = max_delta.max(delta);
+            for value in std::iter::once(self.linear[i])
+                .chain(self.neighbours[i].iter().map(|(_, value)| *value))
+                .filter(|value| *value != 0f64)
+            {
+                min_coefficient = min_coefficient.min(value.abs());
+            }
+        }
+        if max_delta == 0f64 {
+            return (1f64, 1f64);
+        }
+        (
+            2f64.ln() / max_delta,
+            100f64.ln() / min_coefficient.min(max_delta),
+        )
+    }
+}
+
+/// Seeded generator or one from entropy if no seed is given.
+pub(crate) fn rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// Groups equal states into a sample set with energies and occurrence counts.
+pub(crate) fn collect_samples(coo: &COOrdinate, states: Vec<Vec<u8>>) -> SampleSet {
+    let mut counts: BTreeMap<Vec<u8>, usize> = BTreeMap::new();
+    for state in states {
+        *counts.entry(state).or_insert(0) += 1;
+    }
+    let mut sample_set = SampleSet::default();
+    for (sample, count) in counts {
+        sample_set.energies.push(coo.energy(&sample));
+        sample_set.counts.push(count);
+        sample_set.samples.push(sample);
+    }
+    sample_set
+}
+
+impl SimulatedAnnealingSampler {
+    fn betas(&self, adjacency: &Adjacency) -> Vec<f64> {
+        let (start, end) = self
+            .beta_range
+            .unwrap_or_else(|| adjacency.default_beta_range());
+        let steps = self.sweeps.max(1);
+        (0..steps)
+            .map(|step| {
+                let t = if steps == 1 {
+                    1f64
+                } else {
+                    step as f64 / (steps - 1) as f64
+                };
+                match self.schedule {
+                    BetaSchedule::Linear => start + (end - start) * t,
+                    BetaSchedule::Geometric => start * (end / start).powf(t),
+                }
+            })
+            .collect()
+    }
+}
+
+impl QuboSampler for SimulatedAnnealingSampler {
+    fn sample(&self, coo: &COOrdinate, _file_name: &str) -> SampleSet {
+        let adjacency = Adjacency::from(coo);
+        let betas = self.betas(&adjacency);
+        let mut rng = rng(self.seed);
+        let variables = coo.variables();
+
+        let states = (0..self.reads.max(1))
+            .map(|_| {
+                let mut state = (0..variables)
+                    .map(|_| rng.gen_range(0..=1u8))
+                    .collect::<Vec<u8>>();
+                let mut fields = adjacency.fields(&state);
+                for beta in &betas {
+                    adjacency.metropolis_sweep(&mut state, &mut fields, *beta, &mut rng);
+                }
+                state
+            })
+            .collect();
+
+        collect_samples(coo, states)
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    collections::BTreeMap,
+    io::{Error, Write},
+};
+
+/// Sparse upper triangular QUBO matrix.
+///
+/// Every entry is keyed by a variable pair `(i, j)` with `i <= j`, the diagonal
+/// holds the linear terms. The constant offset isn't part of the matrix formats
+/// but is needed to relate energies to the original objective.
+#[derive(Debug, Clone, Default)]
+pub struct COOrdinate {
+    variables: usize,
+    entries: BTreeMap<(usize, usize), f64>,
+    offset: f64,
+}
+
+impl COOrdinate {
+    pub fn new(variables: usize) -> Self {
+        COOrdinate {
+            variables,
+            entries: BTreeMap::new(),
+            offset: 0f64,
+        }
+    }
+
+    /// Number of binary variables.
+    pub fn variables(&self) -> usize {
+        self.variables
+    }
+
+    /// Constant the matrix energy has to be shifted by to get the modelled objective.
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// Adds `value` to the coefficient of `x_i * x_j`, folding it into the upper triangle.
+    pub fn add(&mut self, i: usize, j: usize, value: f64) {
+        if value == 0f64 {
+            return;
+        }
+        let key = if i <= j { (i, j) } else { (j, i) };
+        *self.entries.entry(key).or_insert(0f64) += value;
+    }
+
+    pub fn add_offset(&mut self, value: f64) {
+        self.offset += value;
+    }
+
+    /// Iterates the non zero entries as `(i, j, value)` with `i <= j` in row major order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, f64)> + '_ {
+        self.entries
+            .iter()
+            .filter(|(_, value)| **value != 0f64)
+            .map(|((i, j), value)| (*i, *j, *value))
+    }
+
+    /// Energy of an assignment without the offset.
+    pub fn energy(&self, sample: &[u8]) -> f64 {
+        self.iter()
+            .filter(|(i, j, _)| sample[*i] == 1 && sample[*j] == 1)
+            .map(|(_, _, value)| value)
+            .sum()
+    }
+}
+
+/// A trait to write tsplib95 solution.
+pub trait COOrdinateWriter {
+    /// Writes coordinate matrix to file.
+    fn write_coordinate<W: Write>(&self, writer: &mut W) -> Result<(), Error>;
+}
+
+impl COOrdinateWriter for COOrdinate {
+    fn write_coordinate<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        writeln!(writer, "%%MatrixMarket matrix coordinate real general")?;
+
+        for (i, j, value) in self.iter() {
+            let i = i + 1;
+            let j = j + 1;
+            writeln!(writer, "{i} {j} {value}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use rand::Rng;
+
+use crate::logic::util::log;
+
+use super::{
+    collect_samples, rng, write_qubo_files, Adjacency, COOrdinate, QuboExportFormat, QuboSampler,
+    SampleSet, TabuSampler,
+};
+
+/// qbsolv style decomposition of QUBOs that are too large for a sampler.
+///
+/// A tabu search on the full QUBO gives a start assignment. The variables are
+/// then ordered by how much flipping them would lower the energy and split into
+/// sub-QUBOs of at most `max_variables`, with all other variables clamped to the
+/// current assignment. Improvements of the sub-sampler are merged back and the
+/// tabu search continues from there until `rounds` passes bring no improvement.
+pub struct DecomposingSampler {
+    pub sub_sampler: Box<dyn QuboSampler>,
+    pub max_variables: usize,
+    /// Passes without improvement before the search stops.
+    pub rounds: usize,
+    /// Tabu search length on the full QUBO in multiples of the number of variables.
+    pub sweeps: usize,
+    pub seed: Option<u64>,
+}
+
+/// Sub-QUBO over `variables` with every other variable fixed to its value in `state`.
+fn clamp(adjacency: &Adjacency, state: &[u8], variables: &[usize]) -> COOrdinate {
+    let mut index = vec![usize::MAX; state.len()];
+    for (k, v) in variables.iter().enumerate() {
+        index[*v] = k;
+    }
+
+    let mut sub = COOrdinate::new(variables.len());
+    for (k, v) in variables.iter().enumerate() {
+        sub.add(k, k, adjacency.linear[*v]);
+        for (w, value) in &adjacency.neighbours[*v] {
+            if index[*w] == usize::MAX {
+                if state[*w] == 1 {
+                    sub.add(k, k, *value);
+                }
+            } else if index[*w] > k {
+                sub.add(k, index[*w], *value);
+            }
+        }
+    }
+    sub
+}
+
+impl DecomposingSampler {
+    fn tabu(&self, adjacency: &Adjacency, state: Vec<u8>) -> Vec<u8> {
+        let iterations = self.sweeps.max(1) * state.len();
+        TabuSampler {
+            reads: 1,
+            sweeps: self.sweeps,
+            tenure: None,
+            seed: None,
+        }
+        .search(adjacency, state, iterations)
+    }
+
+    /// Lowest energy sample of the sub-sampler for one sub-QUBO.
+    fn sample_sub(&self, sub: &COOrdinate, file_name: &str) -> Option<Vec<u8>> {
+        let sub_file_name = format!("{file_name}sub.");
+        if self.sub_sampler.reads_files() {
+            write_qubo_files(
+                sub,
+                &sub_file_name,
+                &[QuboExportFormat::Coordinate, QuboExportFormat::DwaveLp],
+            );
+        }
+        let mut sample_set = self.sub_sampler.sample(sub, &sub_file_name);
+        if let Err(e) = sample_set.complete(sub) {
+            println!("QUBO decomposition: ignoring sub sample set: {e}");
+            return None;
+        }
+        sample_set
+            .iter()
+            .min_by(|(_, a, _), (_, b, _)| a.total_cmp(b))
+            .map(|(sample, _, _)| sample.clone())
+    }
+}
+
+impl QuboSampler for DecomposingSampler {
+    fn sample(&self, coo: &COOrdinate, file_name: &str) -> SampleSet {
+        let variables = coo.variables();
+        if variables <= self.max_variables {
+            return self.sub_sampler.sample(coo, file_name);
+        }
+        println!(
+            "{}QUBO decomposition: {variables} variables into sub-QUBOs of at most {}",
+            log::cluster_prefix(),
+            self.max_variables
+        );
+
+        let adjacency = Adjacency::from(coo);
+        let mut rng = rng(self.seed);
+
+        let start = (0..variables)
+            .map(|_| rng.gen_range(0..=1u8))
+            .collect::<Vec<u8>>();
+        let mut state = self.tabu(&adjacency, start);
+        let mut energy = coo.energy(&state);
+        let mut passes_without_improvement = 0;
+
+        while passes_without_improvement < self.rounds.max(1) {
+            let fields = adjacency.fields(&state);
+            let mut order = (0..variables).collect::<Vec<usize>>();
+            order.sort_by(|a, b| {
+                adjacency
+                    .delta(&state, &fields, *a)
+                    .total_cmp(&adjacency.delta(&state, &fields, *b))
+            });
+
+            let mut candidate = state.clone();
+            for chunk in order.chunks(self.max_variables.max(1)) {
+                let sub = clamp(&adjacency, &candidate, chunk);
+                let Some(sub_state) = self.sample_sub(&sub, file_name) else {
+                    continue;
+                };
+                let current = chunk.iter().map(|v| candidate[*v]).collect::<Vec<u8>>();
+                if sub.energy(&sub_state) < sub.energy(&current) {
+                    for (v, value) in chunk.iter().zip(sub_state) {
+                        candidate[*v] = value;
+                    }
+                }
+            }
+            let candidate = self.tabu(&adjacency, candidate);
+            let candidate_energy = coo.energy(&candidate);
+
+            if candidate_energy < energy - 1e-9 {
+                state = candidate;
+                energy = candidate_energy;
+                passes_without_improvement = 0;
+            } else {
+                passes_without_improvement += 1;
+            }
+        }
+        println!(
+            "{}QUBO decomposition: ended with energy {energy}",
+            log::cluster_prefix()
+        );
+
+        collect_samples(coo, vec![state])
+    }
+
+    fn reads_files(&self) -> bool {
+        self.sub_sampler.reads_files()
+    }
+}
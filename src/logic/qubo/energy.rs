@@ -0,0 +1,52 @@
+use std::fmt;
+
+use super::{COOrdinate, TspQuboBuilder};
+
+/// Energy of an assignment split into the terms of the TSP QUBO.
+///
+/// The penalties include their share of the offset, so they are zero iff the
+/// constraints hold and `total` is the modelled objective.
+#[derive(Debug, Clone)]
+pub struct EnergyBreakdown {
+    pub row_penalty: f64,
+    pub column_penalty: f64,
+    /// Weighted tour length over consecutive positions.
+    pub objective: f64,
+    pub total: f64,
+    /// Energy of the QUBO matrix without the offset, as reported by samplers.
+    pub qubo_energy: f64,
+}
+
+impl fmt::Display for EnergyBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "row penalty {}, column penalty {}, objective {}, total {}, qubo energy {}",
+            self.row_penalty, self.column_penalty, self.objective, self.total, self.qubo_energy
+        )
+    }
+}
+
+impl TspQuboBuilder {
+    /// Recomputes the energy of `sample` for penalty `a` and objective weight `b`
+    /// per QUBO term.
+    pub fn energy_breakdown(&self, sample: &[u8], a: f64, b: f64) -> EnergyBreakdown {
+        let term = |add: &dyn Fn(&mut COOrdinate)| {
+            let mut coo = COOrdinate::new(self.variables());
+            add(&mut coo);
+            coo.energy(sample) + coo.offset()
+        };
+        let row_penalty = term(&|coo| self.add_row_constraints(coo, a));
+        let column_penalty = term(&|coo| self.add_column_constraints(coo, a));
+        let objective = term(&|coo| self.add_objective(coo, b));
+
+        let coo = self.build(a, b);
+        EnergyBreakdown {
+            row_penalty,
+            column_penalty,
+            objective,
+            total: row_penalty + column_penalty + objective,
+            qubo_energy: coo.energy(sample),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::logic::util::log;
+
+use super::{collect_samples, Adjacency, COOrdinate, QuboSampler, SampleSet};
+
+/// Largest QUBO whose assignments are enumerated, `2^30` steps already take minutes.
+pub const MAX_ENUMERATED_VARIABLES: usize = 30;
+
+/// Most ground states that are kept, further ones are only counted.
+pub const MAX_STORED_GROUND_STATES: usize = 1000;
+
+/// Lowest energy of a QUBO and the assignments that reach it.
+pub struct GroundStates {
+    pub energy: f64,
+    /// Number of assignments with the lowest energy.
+    pub count: u64,
+    /// The first [`MAX_STORED_GROUND_STATES`] of them.
+    pub states: Vec<Vec<u8>>,
+}
+
+/// Enumerates all `2^n` assignments of a QUBO in Gray code order.
+///
+/// The energy is updated incrementally, so candidates for the minimum get their
+/// energy recomputed before they are compared.
+pub fn ground_states(coo: &COOrdinate) -> GroundStates {
+    let variables = coo.variables();
+    assert!(
+        variables <= MAX_ENUMERATED_VARIABLES,
+        "{variables} variables are too many to enumerate"
+    );
+    let adjacency = Adjacency::from(coo);
+    let mut state = vec![0u8; variables];
+    let mut fields = adjacency.fields(&state);
+    let mut energy = 0f64;
+
+    let mut best = GroundStates {
+        energy: 0f64,
+        count: 1,
+        states: vec![state.clone()],
+    };
+    let tolerance = 1e-9 * coo.iter().map(|(_, _, v)| v.abs()).fold(1f64, f64::max);
+    // generous enough for the rounding errors of the incremental updates
+    let candidate_tolerance = 1e3 * tolerance;
+
+    for step in 1u64..(1u64 << variables) {
+        let i = step.trailing_zeros() as usize;
+        energy += adjacency.delta(&state, &fields, i);
+        adjacency.flip(&mut state, &mut fields, i);
+
+        if energy > best.energy + candidate_tolerance {
+            continue;
+        }
+        energy = coo.energy(&state);
+        if energy < best.energy - tolerance {
+            best.energy = energy;
+            best.count = 1;
+            best.states.clear();
+            best.states.push(state.clone());
+        } else if energy <= best.energy + tolerance {
+            best.count += 1;
+            if best.states.len() < MAX_STORED_GROUND_STATES {
+                best.states.push(state.clone());
+            }
+        }
+    }
+    best
+}
+
+/// Brute force sampler returning all ground states, only feasible for tiny QUBOs.
+pub struct ExhaustiveSampler {
+    pub max_variables: usize,
+}
+
+impl QuboSampler for ExhaustiveSampler {
+    fn sample(&self, coo: &COOrdinate, _file_name: &str) -> SampleSet {
+        if coo.variables() > self.max_variables {
+            println!(
+                "Exhaustive Sampler: {} variables, only up to {} are enumerated",
+                coo.variables(),
+                self.max_variables
+            );
+            std::process::exit(1)
+        }
+        let ground_states = ground_states(coo);
+        if ground_states.count > ground_states.states.len() as u64 {
+            println!(
+                "{}Exhaustive Sampler: {} ground states, returning the first {}",
+                log::cluster_prefix(),
+                ground_states.count,
+                ground_states.states.len()
+            );
+        }
+        collect_samples(coo, ground_states.states)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_more_ground_states_than_it_stores() {
+        // without any terms every assignment has energy 0
+        let ground_states = ground_states(&COOrdinate::new(12));
+        assert_eq!(ground_states.energy, 0.0);
+        assert_eq!(ground_states.count, 1 << 12);
+        assert_eq!(ground_states.states.len(), MAX_STORED_GROUND_STATES);
+    }
+
+    #[test]
+    fn finds_the_single_ground_state() {
+        // -x0 - x1 + 3 x0 x1 is lowest with exactly one variable set, -2 x2 with x2
+        let mut coo = COOrdinate::new(3);
+        coo.add(0, 0, -1.0);
+        coo.add(1, 1, -1.5);
+        coo.add(0, 1, 3.0);
+        coo.add(2, 2, -2.0);
+        let ground_states = ground_states(&coo);
+        assert_eq!(ground_states.energy, -3.5);
+        assert_eq!(ground_states.count, 1);
+        assert_eq!(ground_states.states, vec![vec![0, 1, 1]]);
+    }
+}
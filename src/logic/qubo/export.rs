@@ -0,0 +1,231 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{Error, Write},
+    process::exit,
+};
+
+use serde_json::json;
+
+use super::{write_dwave_lp, COOrdinate, COOrdinateWriter};
+
+/// File formats a QUBO can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuboExportFormat {
+    /// MatrixMarket coordinate matrix, `.coo`
+    Coordinate,
+    /// LP file read by the python qubo solver, `.lp`
+    DwaveLp,
+    /// CPLEX LP with linear and quadratic objective and a `Binary` section, `.qubo.lp`
+    Lp,
+    /// qbsolv input format, `.qubo`
+    Qbsolv,
+    /// dimod serializable binary quadratic model, `.bqm.json`
+    BqmJson,
+    /// Ising fields, couplings and offset, `.ising`
+    Ising,
+    /// Weighted Pauli Z and ZZ strings of the Ising Hamiltonian, `.pauli`
+    Pauli,
+}
+
+impl QuboExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            QuboExportFormat::Coordinate => "coo",
+            QuboExportFormat::DwaveLp => "lp",
+            QuboExportFormat::Lp => "qubo.lp",
+            QuboExportFormat::Qbsolv => "qubo",
+            QuboExportFormat::BqmJson => "bqm.json",
+            QuboExportFormat::Ising => "ising",
+            QuboExportFormat::Pauli => "pauli",
+        }
+    }
+}
+
+/// Ising model `sum_i h_i s_i + sum_{i<j} J_ij s_i s_j + offset` with spins in {-1, 1}.
+#[derive(Debug, Clone)]
+pub struct IsingModel {
+    pub h: Vec<f64>,
+    pub j: BTreeMap<(usize, usize), f64>,
+    pub offset: f64,
+}
+
+impl From<&COOrdinate> for IsingModel {
+    /// Substitutes `x = (1 + s) / 2`, the offset of the QUBO is kept.
+    fn from(coo: &COOrdinate) -> Self {
+        let mut h = vec![0f64; coo.variables()];
+        let mut j = BTreeMap::new();
+        let mut offset = coo.offset();
+        for (a, b, value) in coo.iter() {
+            if a == b {
+                h[a] += value / 2f64;
+                offset += value / 2f64;
+            } else {
+                h[a] += value / 4f64;
+                h[b] += value / 4f64;
+                *j.entry((a, b)).or_insert(0f64) += value / 4f64;
+                offset += value / 4f64;
+            }
+        }
+        IsingModel { h, j, offset }
+    }
+}
+
+/// A trait to write a QUBO for external QUBO tools.
+pub trait QuboWriter {
+    /// Writes the qbsolv `.qubo` format with 0 based variables.
+    fn write_qbsolv<W: Write>(&self, writer: &mut W) -> Result<(), Error>;
+    /// Writes the dimod serializable BQM, including the offset.
+    fn write_bqm_json<W: Write>(&self, writer: &mut W) -> Result<(), Error>;
+    /// Writes the Ising model as `offset`, `h i value` and `J i j value` lines.
+    fn write_ising<W: Write>(&self, writer: &mut W) -> Result<(), Error>;
+    /// Writes one `coefficient pauli-string` line per term of the Ising Hamiltonian.
+    fn write_pauli<W: Write>(&self, writer: &mut W) -> Result<(), Error>;
+    /// Writes a CPLEX LP with binary variables `x1..xn`.
+    fn write_lp<W: Write>(&self, writer: &mut W) -> Result<(), Error>;
+}
+
+impl QuboWriter for COOrdinate {
+    fn write_qbsolv<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let (nodes, couplers): (Vec<_>, Vec<_>) = self.iter().partition(|(i, j, _)| i == j);
+        writeln!(writer, "c QUBO from TSP, offset {}", self.offset())?;
+        writeln!(
+            writer,
+            "p qubo 0 {} {} {}",
+            self.variables(),
+            nodes.len(),
+            couplers.len()
+        )?;
+        for (i, j, value) in nodes.iter().chain(couplers.iter()) {
+            writeln!(writer, "{i} {j} {value}")?;
+        }
+        Ok(())
+    }
+
+    fn write_bqm_json<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut linear = vec![0f64; self.variables()];
+        let mut heads = vec![];
+        let mut tails = vec![];
+        let mut biases = vec![];
+        for (i, j, value) in self.iter() {
+            if i == j {
+                linear[i] += value;
+            } else {
+                heads.push(i);
+                tails.push(j);
+                biases.push(value);
+            }
+        }
+        let bqm = json!({
+            "type": "BinaryQuadraticModel",
+            "version": {"bqm_schema": "3.0.0"},
+            "use_bytes": false,
+            "index_type": "int32",
+            "bias_type": "float64",
+            "num_variables": self.variables(),
+            "num_interactions": biases.len(),
+            "variable_labels": (0..self.variables()).collect::<Vec<usize>>(),
+            "variable_type": "BINARY",
+            "offset": self.offset(),
+            "info": {},
+            "linear_biases": linear,
+            "quadratic_biases": biases,
+            "quadratic_head": heads,
+            "quadratic_tail": tails,
+        });
+        serde_json::to_writer(writer, &bqm).map_err(Error::from)
+    }
+
+    fn write_ising<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let ising = IsingModel::from(self);
+        writeln!(writer, "offset {}", ising.offset)?;
+        for (i, value) in ising.h.iter().enumerate() {
+            if *value != 0f64 {
+                writeln!(writer, "h {i} {value}")?;
+            }
+        }
+        for ((i, j), value) in ising.j.iter() {
+            writeln!(writer, "J {i} {j} {value}")?;
+        }
+        Ok(())
+    }
+
+    fn write_pauli<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        for (coefficient, label) in IsingModel::from(self).pauli_terms() {
+            writeln!(writer, "{coefficient} {label}")?;
+        }
+        Ok(())
+    }
+
+    fn write_lp<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let term = |value: f64, first: bool| {
+            if value < 0f64 {
+                format!(" - {}", -value)
+            } else if first {
+                format!(" {value}")
+            } else {
+                format!(" + {value}")
+            }
+        };
+
+        let mut objective = String::new();
+        for (i, _, value) in self.iter().filter(|(i, j, _)| i == j) {
+            objective += &term(value, objective.is_empty());
+            objective += &format!(" x{}", i + 1);
+        }
+        let mut quadratic = String::new();
+        for (i, j, value) in self.iter().filter(|(i, j, _)| i != j) {
+            quadratic += &term(2f64 * value, quadratic.is_empty());
+            quadratic += &format!(" x{} * x{}", i + 1, j + 1);
+        }
+        if !quadratic.is_empty() {
+            objective += if objective.is_empty() { " [" } else { " + [" };
+            objective += &quadratic;
+            objective += " ] / 2";
+        }
+        if objective.is_empty() {
+            objective = String::from(" 0");
+        }
+
+        writeln!(
+            writer,
+            "\\ QUBO from TSP, constant offset {}",
+            self.offset()
+        )?;
+        writeln!(writer, "Minimize")?;
+        writeln!(writer, " obj:{objective}")?;
+        writeln!(writer, "Subject To")?;
+        writeln!(writer, "Binary")?;
+        for i in 0..self.variables() {
+            writeln!(writer, " x{}", i + 1)?;
+        }
+        writeln!(writer, "End")
+    }
+}
+
+/// Writes the QUBO to `{file_name}{extension}` for every format.
+pub fn write_qubo_files(coo: &COOrdinate, file_name: &str, formats: &[QuboExportFormat]) {
+    for format in formats {
+        let path = format!("{}{}", file_name, format.extension());
+        let mut file = match File::create(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                println!("Problem opening qubo file {path}: {e}");
+                exit(1)
+            }
+        };
+        let written = match format {
+            QuboExportFormat::Coordinate => coo.write_coordinate(&mut file),
+            QuboExportFormat::DwaveLp => write_dwave_lp(coo, &mut file),
+            QuboExportFormat::Lp => coo.write_lp(&mut file),
+            QuboExportFormat::Qbsolv => coo.write_qbsolv(&mut file),
+            QuboExportFormat::BqmJson => coo.write_bqm_json(&mut file),
+            QuboExportFormat::Ising => coo.write_ising(&mut file),
+            QuboExportFormat::Pauli => coo.write_pauli(&mut file),
+        };
+        if let Err(e) = written {
+            println!("Problem writing qubo file {path}: {e}");
+            exit(1)
+        }
+    }
+}
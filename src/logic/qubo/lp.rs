@@ -0,0 +1,54 @@
+use std::io::{Error, Write};
+
+use lp_solvers::{
+    lp_format::{LpObjective, LpProblem},
+    problem::{Problem, StrExpression, Variable},
+};
+
+use super::COOrdinate;
+
+pub fn convert_tsp_to_lp(coo: &COOrdinate) -> Problem<StrExpression, Variable> {
+    // hacky way to make format work with dwave is to multiply by 2 and divide by 2 and also add 0 in front
+    let objective_str = coo
+        .iter()
+        .map(|(i, j, value)| format!("{} x{} * x{}", value * 2f64, i + 1, j + 1))
+        .collect::<Vec<String>>()
+        .join(" + ");
+
+    let objective_str = objective_str.replace("+ -", "- ");
+
+    Problem {
+        // Alternatively, you can implement the LpProblem trait on your own structure
+        name: "QUBO from TSP".to_string(),
+        sense: LpObjective::Minimize,
+        objective: StrExpression(format!("0 + [ {objective_str} ] / 2")), // You can use other expression representations
+        variables: (0..coo.variables())
+            .map(|i| Variable {
+                name: format!("x{}", i + 1),
+                is_integer: true,
+                lower_bound: 0.,
+                upper_bound: 2.,
+            })
+            .collect(),
+        constraints: vec![],
+    }
+}
+
+/// Writes the LP file the D-Wave tooling of the python qubo solver reads.
+///
+/// The display of the LP problem is cut before its bounds and the variables are
+/// declared binary instead.
+pub fn write_dwave_lp<W: Write>(coo: &COOrdinate, writer: &mut W) -> Result<(), Error> {
+    let lp = convert_tsp_to_lp(coo);
+    let lp_disp = lp.display_lp();
+    let lp_disp = lp_disp.to_string();
+    let lp_disp = &lp_disp[0..lp_disp.find("Bounds").unwrap()];
+    let lp_vars = lp
+        .variables
+        .iter()
+        .map(|x| x.name.clone())
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    write!(writer, "{lp_disp} \n\nBinary\n{lp_vars}\nEnd")
+}
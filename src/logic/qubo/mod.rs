@@ -0,0 +1,33 @@
+mod annealing;
+mod coordinate;
+mod decomposition;
+mod energy;
+mod exhaustive;
+mod export;
+mod lp;
+mod parameters;
+mod pauli;
+mod qaoa;
+mod sample_set;
+mod sampler;
+mod tabu;
+mod tempering;
+mod tsp;
+mod verify;
+
+pub use annealing::*;
+pub use coordinate::*;
+pub use decomposition::*;
+pub use energy::*;
+pub use exhaustive::*;
+pub use export::*;
+pub use lp::*;
+pub use parameters::*;
+pub use pauli::*;
+pub use qaoa::*;
+pub use sample_set::*;
+pub use sampler::*;
+pub use tabu::*;
+pub use tempering::*;
+pub use tsp::*;
+pub use verify::*;
@@ -0,0 +1,49 @@
+use std::{fs, process::exit};
+
+use serde::{Deserialize, Serialize};
+
+/// Weights of the TSP QUBO `a * constraints + b * tour length`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuboParameters {
+    /// Constraint penalty `a`, defaults to `size * b * max_distance`.
+    pub penalty: Option<f64>,
+    /// Weight `b` of the tour length.
+    pub objective_weight: f64,
+    /// Start at the lower bound `b * max_distance` and raise the penalty until
+    /// the sample is feasible, at most up to the default penalty.
+    pub auto_penalty: bool,
+    /// Factor the penalty is raised by while tuning.
+    pub penalty_factor: f64,
+}
+
+impl Default for QuboParameters {
+    fn default() -> Self {
+        QuboParameters {
+            penalty: None,
+            objective_weight: 1f64,
+            auto_penalty: false,
+            penalty_factor: 2f64,
+        }
+    }
+}
+
+impl QuboParameters {
+    /// Reads the parameters from a TOML file, missing keys keep their defaults.
+    pub fn from_file(path: &str) -> Self {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                println!("Problem opening qubo config {path}: {e}");
+                exit(1)
+            }
+        };
+        match toml::from_str(&content) {
+            Ok(parameters) => parameters,
+            Err(e) => {
+                println!("Problem parsing qubo config {path}: {e}");
+                exit(1)
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::{fs::File, process::exit};
+
+use serde::Serialize;
+
+use super::{IsingModel, QuboFormulation, TspQuboBuilder};
+
+impl IsingModel {
+    /// Weighted Pauli strings of the Hamiltonian with `x_i = (1 - Z_i) / 2`.
+    ///
+    /// Qubit 0 is the rightmost character, the identity string carries the offset.
+    pub fn pauli_terms(&self) -> Vec<(f64, String)> {
+        let qubits = self.h.len();
+        let label = |set: &[usize]| {
+            (0..qubits)
+                .rev()
+                .map(|qubit| if set.contains(&qubit) { 'Z' } else { 'I' })
+                .collect::<String>()
+        };
+
+        let mut terms = vec![(self.offset, label(&[]))];
+        // spins of the Ising model are `s_i = 2 x_i - 1 = -Z_i`
+        for (qubit, value) in self.h.iter().enumerate() {
+            if *value != 0f64 {
+                terms.push((-value, label(&[qubit])));
+            }
+        }
+        for ((i, j), value) in self.j.iter() {
+            terms.push((*value, label(&[*i, *j])));
+        }
+        terms
+    }
+}
+
+/// Maps the qubits of the Pauli strings back to the TSP.
+#[derive(Debug, Clone, Serialize)]
+pub struct QubitManifest {
+    pub formulation: String,
+    pub depot: usize,
+    /// Number of encoded cities and tour positions.
+    pub size: usize,
+    pub qubits: usize,
+    pub penalty: f64,
+    pub objective_weight: f64,
+    /// Constant the QUBO matrix energy has to be shifted by.
+    pub offset: f64,
+    pub variables: Vec<QubitAssignment>,
+}
+
+/// Qubit that is one if node `city` is visited at tour `position`.
+#[derive(Debug, Clone, Serialize)]
+pub struct QubitAssignment {
+    pub qubit: usize,
+    pub city: usize,
+    pub position: usize,
+}
+
+impl TspQuboBuilder {
+    /// Manifest of the QUBO built with penalty `a` and objective weight `b`.
+    pub fn qubit_manifest(&self, a: f64, b: f64) -> QubitManifest {
+        let mut variables = Vec::with_capacity(self.variables());
+        for (city, id) in self.cities().iter().enumerate() {
+            for position in 0..self.size() {
+                variables.push(QubitAssignment {
+                    qubit: self.variable(city, position),
+                    city: *id,
+                    position,
+                });
+            }
+        }
+
+        QubitManifest {
+            formulation: match self.formulation() {
+                QuboFormulation::Full => String::from("full"),
+                QuboFormulation::DepotFixed => String::from("depot-fixed"),
+            },
+            depot: self.depot(),
+            size: self.size(),
+            qubits: self.variables(),
+            penalty: a,
+            objective_weight: b,
+            offset: self.build(a, b).offset(),
+            variables,
+        }
+    }
+
+    /// Writes the qubit manifest to `{file_name}qubits.json`.
+    pub fn write_qubit_manifest(&self, a: f64, b: f64, file_name: &str) {
+        let path = format!("{file_name}qubits.json");
+        let file = match File::create(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                println!("Problem opening qubit manifest {path}: {e}");
+                exit(1)
+            }
+        };
+        if let Err(e) = serde_json::to_writer_pretty(file, &self.qubit_manifest(a, b)) {
+            println!("Problem writing qubit manifest {path}: {e}");
+            exit(1)
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use std::{f64::consts::PI, process::exit};
+
+use rand::Rng;
+
+use crate::logic::util::log;
+
+use super::{collect_samples, rng, Adjacency, COOrdinate, QuboSampler, SampleSet};
+
+/// Largest QUBO that is simulated, its statevector and cost diagonal take about 400 MB.
+pub const MAX_SIMULATED_QUBITS: usize = 24;
+
+/// Samples the QUBO from a simulated QAOA state with `layers` cost and mixer layers.
+///
+/// The statevector over all `2^n` bitstrings is simulated on the CPU, so the QUBO may
+/// have at most `max_qubits` variables and never more than [`MAX_SIMULATED_QUBITS`]. The angles are optimized with Nelder-Mead on
+/// the expected energy before `reads` bitstrings are drawn from the final state.
+pub struct QaoaSampler {
+    pub layers: usize,
+    pub reads: usize,
+    /// Maximum number of energy evaluations of the angle optimization.
+    pub iterations: usize,
+    pub max_qubits: usize,
+    pub seed: Option<u64>,
+}
+
+/// Amplitudes of the `2^n` basis states, bit `k` of the index is variable `k`.
+struct Statevector {
+    re: Vec<f64>,
+    im: Vec<f64>,
+}
+
+impl Statevector {
+    /// Uniform superposition, the ground state of the mixer.
+    fn plus(qubits: usize) -> Self {
+        let states = 1usize << qubits;
+        let amplitude = 1f64 / (states as f64).sqrt();
+        Statevector {
+            re: vec![amplitude; states],
+            im: vec![0f64; states],
+        }
+    }
+
+    /// Applies `exp(-i gamma C)` for the diagonal cost `C`.
+    fn phase(&mut self, cost: &[f64], gamma: f64) {
+        for (z, c) in cost.iter().enumerate() {
+            let (sin, cos) = (-gamma * c).sin_cos();
+            let (re, im) = (self.re[z], self.im[z]);
+            self.re[z] = re * cos - im * sin;
+            self.im[z] = re * sin + im * cos;
+        }
+    }
+
+    /// Applies `exp(-i beta X)` to every qubit.
+    fn mix(&mut self, qubits: usize, beta: f64) {
+        let (sin, cos) = beta.sin_cos();
+        for qubit in 0..qubits {
+            let bit = 1usize << qubit;
+            for z in (0..self.re.len()).filter(|z| z & bit == 0) {
+                let (re0, im0) = (self.re[z], self.im[z]);
+                let (re1, im1) = (self.re[z | bit], self.im[z | bit]);
+                self.re[z] = cos * re0 + sin * im1;
+                self.im[z] = cos * im0 - sin * re1;
+                self.re[z | bit] = cos * re1 + sin * im0;
+                self.im[z | bit] = cos * im1 - sin * re0;
+            }
+        }
+    }
+
+    fn probabilities(&self) -> Vec<f64> {
+        self.re
+            .iter()
+            .zip(self.im.iter())
+            .map(|(re, im)| re * re + im * im)
+            .collect()
+    }
+}
+
+/// Energies of all bitstrings, enumerated in Gray code order.
+fn energies(coo: &COOrdinate) -> Vec<f64> {
+    let variables = coo.variables();
+    let adjacency = Adjacency::from(coo);
+    let mut state = vec![0u8; variables];
+    let mut fields = adjacency.fields(&state);
+    let mut energy = 0f64;
+    let mut energies = vec![0f64; 1 << variables];
+
+    for step in 1usize..(1 << variables) {
+        let i = step.trailing_zeros() as usize;
+        energy += adjacency.delta(&state, &fields, i);
+        adjacency.flip(&mut state, &mut fields, i);
+        energies[step ^ (step >> 1)] = energy;
+    }
+    energies
+}
+
+/// QAOA state for the angles `[gamma_1, beta_1, ..., gamma_p, beta_p]`.
+fn qaoa_state(cost: &[f64], qubits: usize, angles: &[f64]) -> Statevector {
+    let mut state = Statevector::plus(qubits);
+    for layer in angles.chunks(2) {
+        state.phase(cost, layer[0]);
+        state.mix(qubits, layer[1]);
+    }
+    state
+}
+
+fn expectation(cost: &[f64], qubits: usize, angles: &[f64]) -> f64 {
+    qaoa_state(cost, qubits, angles)
+        .probabilities()
+        .iter()
+        .zip(cost.iter())
+        .map(|(p, c)| p * c)
+        .sum()
+}
+
+/// Minimizes `f` with the Nelder-Mead simplex method within `evaluations` calls.
+fn nelder_mead<F: Fn(&[f64]) -> f64>(
+    f: F,
+    start: Vec<f64>,
+    step: f64,
+    evaluations: usize,
+) -> (Vec<f64>, f64) {
+    let dim = start.len();
+    let mut simplex = vec![(start.clone(), f(&start))];
+    for k in 0..dim {
+        let mut point = start.clone();
+        point[k] += step;
+        let value = f(&point);
+        simplex.push((point, value));
+    }
+    let mut evaluated = dim + 1;
+
+    while evaluated < evaluations {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let spread = simplex[dim].1 - simplex[0].1;
+        if spread.abs() < 1e-10 {
+            break;
+        }
+
+        let centroid = (0..dim)
+            .map(|k| simplex[..dim].iter().map(|(p, _)| p[k]).sum::<f64>() / dim as f64)
+            .collect::<Vec<f64>>();
+        let towards = |t: f64| {
+            centroid
+                .iter()
+                .zip(simplex[dim].0.iter())
+                .map(|(c, w)| c + t * (w - c))
+                .collect::<Vec<f64>>()
+        };
+
+        let reflected = towards(-1f64);
+        let reflected_value = f(&reflected);
+        evaluated += 1;
+
+        if reflected_value < simplex[0].1 {
+            let expanded = towards(-2f64);
+            let expanded_value = f(&expanded);
+            evaluated += 1;
+            simplex[dim] = if expanded_value < reflected_value {
+                (expanded, expanded_value)
+            } else {
+                (reflected, reflected_value)
+            };
+        } else if reflected_value < simplex[dim - 1].1 {
+            simplex[dim] = (reflected, reflected_value);
+        } else {
+            let contracted = if reflected_value < simplex[dim].1 {
+                towards(-0.5)
+            } else {
+                towards(0.5)
+            };
+            let contracted_value = f(&contracted);
+            evaluated += 1;
+            if contracted_value < reflected_value.min(simplex[dim].1) {
+                simplex[dim] = (contracted, contracted_value);
+            } else {
+                // shrink towards the best point
+                let best = simplex[0].0.clone();
+                for (point, value) in simplex.iter_mut().skip(1) {
+                    for (x, b) in point.iter_mut().zip(best.iter()) {
+                        *x = b + 0.5 * (*x - b);
+                    }
+                    *value = f(point);
+                }
+                evaluated += dim;
+            }
+        }
+    }
+
+    simplex
+        .into_iter()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+impl QuboSampler for QaoaSampler {
+    fn sample(&self, coo: &COOrdinate, _file_name: &str) -> SampleSet {
+        let qubits = coo.variables();
+        let max_qubits = self.max_qubits.min(MAX_SIMULATED_QUBITS);
+        if qubits > max_qubits {
+            println!("QAOA Sampler: {qubits} qubits, only up to {max_qubits} are simulated");
+            exit(1)
+        }
+
+        // scale the cost so the same angles fit QUBOs of any magnitude
+        let mut cost = energies(coo);
+        let mean = cost.iter().sum::<f64>() / cost.len() as f64;
+        let scale = cost
+            .iter()
+            .map(|c| (c - mean).abs())
+            .fold(0f64, f64::max)
+            .max(f64::MIN_POSITIVE);
+        for c in cost.iter_mut() {
+            *c = (*c - mean) / scale;
+        }
+
+        // linear ramp from the mixer to the cost Hamiltonian as starting point
+        let layers = self.layers.max(1);
+        let start = (0..layers)
+            .flat_map(|k| {
+                let t = (k as f64 + 0.5) / layers as f64;
+                [t * PI / 2f64, (1f64 - t) * PI / 4f64]
+            })
+            .collect::<Vec<f64>>();
+        let (angles, value) = nelder_mead(
+            |angles| expectation(&cost, qubits, angles),
+            start,
+            0.1,
+            self.iterations,
+        );
+        println!(
+            "{}QAOA Sampler: {layers} layers, expected energy {}",
+            log::cluster_prefix(),
+            value * scale + mean
+        );
+
+        let probabilities = qaoa_state(&cost, qubits, &angles).probabilities();
+        let mut cumulative = probabilities
+            .iter()
+            .scan(0f64, |sum, p| {
+                *sum += p;
+                Some(*sum)
+            })
+            .collect::<Vec<f64>>();
+        let total = *cumulative.last().unwrap();
+        for c in cumulative.iter_mut() {
+            *c /= total;
+        }
+
+        let mut rng = rng(self.seed);
+        let states = (0..self.reads)
+            .map(|_| {
+                let r: f64 = rng.gen();
+                let z = cumulative
+                    .partition_point(|c| *c < r)
+                    .min(cumulative.len() - 1);
+                (0..qubits).map(|k| ((z >> k) & 1) as u8).collect()
+            })
+            .collect();
+        collect_samples(coo, states)
+    }
+}
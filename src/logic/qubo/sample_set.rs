@@ -0,0 +1,121 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::COOrdinate;
+
+/// Samples of a QUBO solver with their energies and number of occurrences.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SampleSet {
+    pub samples: Vec<Vec<u8>>,
+    #[serde(default)]
+    pub energies: Vec<f64>,
+    #[serde(default)]
+    pub counts: Vec<usize>,
+}
+
+impl SampleSet {
+    /// Parses a JSON sample set or, for older solver outputs, a single bitstring
+    /// with one bit per whitespace separated token.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        if content.trim_start().starts_with('{') {
+            return serde_json::from_str(content).map_err(|e| e.to_string());
+        }
+        let sample = content
+            .split_whitespace()
+            .map(|x| match x {
+                "0" => Ok(0),
+                "1" => Ok(1),
+                _ => Err(format!("bitstring token {x:?} is neither 0 nor 1")),
+            })
+            .collect::<Result<Vec<u8>, String>>()?;
+        Ok(SampleSet {
+            samples: vec![sample],
+            energies: vec![],
+            counts: vec![],
+        })
+    }
+
+    /// Checks the sample sizes and fills in missing energies and counts.
+    pub fn complete(&mut self, coo: &COOrdinate) -> Result<(), String> {
+        if let Some(sample) = self
+            .samples
+            .iter()
+            .find(|sample| sample.len() != coo.variables())
+        {
+            return Err(format!(
+                "sample has {} variables, the QUBO has {}",
+                sample.len(),
+                coo.variables()
+            ));
+        }
+        if self.energies.len() != self.samples.len() {
+            self.energies = self
+                .samples
+                .iter()
+                .map(|sample| coo.energy(sample))
+                .collect();
+        }
+        if self.counts.len() != self.samples.len() {
+            self.counts = vec![1; self.samples.len()];
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Vec<u8>, f64, usize)> + '_ {
+        self.samples
+            .iter()
+            .zip(self.energies.iter().copied())
+            .zip(self.counts.iter().copied())
+            .map(|((sample, energy), count)| (sample, energy, count))
+    }
+}
+
+/// Summary of a sample set and the sample a tour was taken from.
+#[derive(Debug, Clone)]
+pub struct SampleStatistics {
+    /// Number of reads, counting repeated samples.
+    pub reads: usize,
+    /// Fraction of the reads that satisfy all constraints.
+    pub feasible_fraction: f64,
+    pub best_energy: f64,
+    pub chosen_energy: f64,
+    pub chosen_length: f64,
+    pub repaired: bool,
+}
+
+impl fmt::Display for SampleStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "reads: {}", self.reads)?;
+        writeln!(f, "feasible_fraction: {}", self.feasible_fraction)?;
+        writeln!(f, "best_energy: {}", self.best_energy)?;
+        writeln!(f, "chosen_energy: {}", self.chosen_energy)?;
+        writeln!(f, "chosen_length: {}", self.chosen_length)?;
+        writeln!(f, "repaired: {}", self.repaired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_bitstring() {
+        let sample_set = SampleSet::parse("1 0\n0 1\n").unwrap();
+        assert_eq!(sample_set.samples, vec![vec![1, 0, 0, 1]]);
+    }
+
+    #[test]
+    fn rejects_other_bitstring_tokens() {
+        for token in ["1.0", "True", "-1", "x"] {
+            assert!(
+                SampleSet::parse(&format!("0 {token} 1")).is_err(),
+                "{token}"
+            );
+        }
+    }
+}
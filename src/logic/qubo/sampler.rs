@@ -0,0 +1,13 @@
+use super::{COOrdinate, SampleSet};
+
+/// Samples low energy assignments of a QUBO.
+pub trait QuboSampler: Send + Sync {
+    /// Samples the QUBO, `file_name` is the path prefix the `.coo` and `.lp` files
+    /// of the QUBO were written to.
+    fn sample(&self, coo: &COOrdinate, file_name: &str) -> SampleSet;
+
+    /// Whether the sampler reads the QUBO from the written files instead of `coo`.
+    fn reads_files(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,86 @@
+use rand::Rng;
+
+use super::{collect_samples, rng, Adjacency, COOrdinate, QuboSampler, SampleSet};
+
+/// Multistart single-flip tabu search over a QUBO.
+///
+/// Every read starts from a random assignment and always takes the best flip
+/// that isn't tabu, a tabu flip is only allowed if it improves on the best
+/// energy of the read. Returns the best assignment of every read.
+pub struct TabuSampler {
+    pub reads: usize,
+    /// Flips per read in multiples of the number of variables.
+    pub sweeps: usize,
+    /// Number of flips a flipped variable stays tabu, `min(20, variables / 4)` if not set.
+    pub tenure: Option<usize>,
+    pub seed: Option<u64>,
+}
+
+impl TabuSampler {
+    /// Runs one tabu search from `state` and returns the best assignment found.
+    pub(crate) fn search(
+        &self,
+        adjacency: &Adjacency,
+        mut state: Vec<u8>,
+        iterations: usize,
+    ) -> Vec<u8> {
+        let variables = state.len();
+        if variables == 0 {
+            return state;
+        }
+        let tenure = self
+            .tenure
+            .unwrap_or_else(|| 20.min(variables / 4))
+            .min(variables - 1);
+
+        let mut fields = adjacency.fields(&state);
+        let mut tabu_until = vec![0usize; variables];
+        let mut energy = 0f64;
+        let mut best_energy = 0f64;
+        let mut best_state = state.clone();
+
+        for step in 1..=iterations {
+            let mut chosen: Option<(usize, f64)> = None;
+            for (i, until) in tabu_until.iter().enumerate() {
+                let delta = adjacency.delta(&state, &fields, i);
+                let allowed = *until < step || energy + delta < best_energy - 1e-9;
+                if allowed && chosen.map_or(true, |(_, best)| delta < best) {
+                    chosen = Some((i, delta));
+                }
+            }
+            let Some((i, delta)) = chosen else {
+                break;
+            };
+
+            adjacency.flip(&mut state, &mut fields, i);
+            energy += delta;
+            tabu_until[i] = step + tenure;
+
+            if energy < best_energy - 1e-9 {
+                best_energy = energy;
+                best_state.copy_from_slice(&state);
+            }
+        }
+        best_state
+    }
+}
+
+impl QuboSampler for TabuSampler {
+    fn sample(&self, coo: &COOrdinate, _file_name: &str) -> SampleSet {
+        let adjacency = Adjacency::from(coo);
+        let mut rng = rng(self.seed);
+        let variables = coo.variables();
+        let iterations = self.sweeps.max(1) * variables;
+
+        let states = (0..self.reads.max(1))
+            .map(|_| {
+                let state = (0..variables)
+                    .map(|_| rng.gen_range(0..=1u8))
+                    .collect::<Vec<u8>>();
+                self.search(&adjacency, state, iterations)
+            })
+            .collect();
+
+        collect_samples(coo, states)
+    }
+}
@@ -0,0 +1,86 @@
+use rand::Rng;
+
+use super::{collect_samples, rng, Adjacency, COOrdinate, QuboSampler, SampleSet};
+
+/// Parallel tempering (replica exchange Monte Carlo) over a QUBO.
+///
+/// Replicas at geometrically spaced inverse temperatures do Metropolis sweeps
+/// and neighbouring replicas swap their states after every sweep. Returns the
+/// lowest energy assignment any replica visited in a read.
+pub struct ParallelTemperingSampler {
+    pub reads: usize,
+    pub sweeps: usize,
+    pub replicas: usize,
+    /// Hottest and coldest inverse temperature, derived from the QUBO if not set.
+    pub beta_range: Option<(f64, f64)>,
+    pub seed: Option<u64>,
+}
+
+impl QuboSampler for ParallelTemperingSampler {
+    fn sample(&self, coo: &COOrdinate, _file_name: &str) -> SampleSet {
+        let adjacency = Adjacency::from(coo);
+        let mut rng = rng(self.seed);
+        let variables = coo.variables();
+
+        let replicas = self.replicas.max(2);
+        let (hot, cold) = self
+            .beta_range
+            .unwrap_or_else(|| adjacency.default_beta_range());
+        let betas = (0..replicas)
+            .map(|r| hot * (cold / hot).powf(r as f64 / (replicas - 1) as f64))
+            .collect::<Vec<f64>>();
+
+        let states = (0..self.reads.max(1))
+            .map(|_| {
+                let mut states = (0..replicas)
+                    .map(|_| {
+                        (0..variables)
+                            .map(|_| rng.gen_range(0..=1u8))
+                            .collect::<Vec<u8>>()
+                    })
+                    .collect::<Vec<Vec<u8>>>();
+                let mut fields = states
+                    .iter()
+                    .map(|state| adjacency.fields(state))
+                    .collect::<Vec<Vec<f64>>>();
+                let mut energies = states
+                    .iter()
+                    .map(|state| coo.energy(state))
+                    .collect::<Vec<f64>>();
+
+                let (mut best_energy, mut best_state) = energies
+                    .iter()
+                    .zip(states.iter())
+                    .min_by(|(a, _), (b, _)| a.total_cmp(b))
+                    .map(|(energy, state)| (*energy, state.clone()))
+                    .unwrap();
+
+                for _ in 0..self.sweeps {
+                    for r in 0..replicas {
+                        energies[r] += adjacency.metropolis_sweep(
+                            &mut states[r],
+                            &mut fields[r],
+                            betas[r],
+                            &mut rng,
+                        );
+                        if energies[r] < best_energy {
+                            best_energy = energies[r];
+                            best_state.copy_from_slice(&states[r]);
+                        }
+                    }
+                    for r in 0..replicas - 1 {
+                        let exponent = (betas[r] - betas[r + 1]) * (energies[r] - energies[r + 1]);
+                        if exponent >= 0f64 || rng.gen::<f64>() < exponent.exp() {
+                            states.swap(r, r + 1);
+                            fields.swap(r, r + 1);
+                            energies.swap(r, r + 1);
+                        }
+                    }
+                }
+                best_state
+            })
+            .collect();
+
+        collect_samples(coo, states)
+    }
+}
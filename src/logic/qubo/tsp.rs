@@ -0,0 +1,374 @@
+use std::process::exit;
+
+use tspf::Tsp;
+
+use crate::logic::util::{tour::DistanceMatrix, tsp::Distancing};
+
+use super::{COOrdinate, SampleSet, SampleStatistics};
+
+/// Which nodes of the TSP get encoded as binary variables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuboFormulation {
+    /// Every node is encoded with one variable per tour position, `dim²` variables.
+    Full,
+    /// The depot is pinned to position 0 and only the customers are encoded,
+    /// `(dim - 1)²` variables. The edges from and to the depot become linear terms.
+    DepotFixed,
+}
+
+/// Builds the QUBO of a TSP with one binary variable `x_{city, position}` per
+/// encoded city and tour position.
+///
+/// The nodes of the TSP have to be numbered `1..=dim`.
+pub struct TspQuboBuilder {
+    formulation: QuboFormulation,
+    depot: usize,
+    /// Node ids of the encoded cities, city `i` of the QUBO is node `cities[i]`.
+    cities: Vec<usize>,
+    matrix: DistanceMatrix,
+    max_distance: f64,
+}
+
+/// A tour decoded from a QUBO sample.
+#[derive(Debug, Clone)]
+pub struct DecodedTour {
+    /// Node ids of the tour, starting with the depot if it is part of the tour.
+    pub tour: Vec<usize>,
+    /// Number of cities that aren't visited at exactly one position.
+    pub row_violations: usize,
+    /// Number of positions that aren't used by exactly one city.
+    pub column_violations: usize,
+    /// Whether the sample violated constraints and the tour had to be repaired.
+    pub repaired: bool,
+}
+
+impl TspQuboBuilder {
+    pub fn new(tsp: &Tsp, formulation: QuboFormulation) -> Self {
+        if tsp.demands().values().sum::<f64>() > tsp.capacity() {
+            println!("CVRP subproblem can't be solved with this");
+            exit(1);
+        }
+
+        let dim = tsp.dim();
+
+        let mut max_distance: f64 = 0f64;
+        let mut distances = vec![vec![0f64; dim]; dim];
+
+        for u in 1..=dim {
+            for i in 1..=dim {
+                let dist = tsp.distance(u, i).unwrap();
+                if dist > max_distance {
+                    max_distance = dist;
+                }
+                distances[u - 1][i - 1] = dist;
+            }
+        }
+
+        let depot = tsp.depots().iter().min().copied().unwrap_or(1);
+        let cities = match formulation {
+            QuboFormulation::Full => (1..=dim).collect(),
+            QuboFormulation::DepotFixed => (1..=dim).filter(|id| *id != depot).collect(),
+        };
+
+        TspQuboBuilder {
+            formulation,
+            depot,
+            cities,
+            matrix: DistanceMatrix {
+                ids: (1..=dim).collect(),
+                distances,
+            },
+            max_distance,
+        }
+    }
+
+    /// Number of encoded cities, which is also the number of tour positions.
+    pub fn size(&self) -> usize {
+        self.cities.len()
+    }
+
+    /// Number of binary variables of the QUBO.
+    pub fn variables(&self) -> usize {
+        self.size() * self.size()
+    }
+
+    /// Index of the variable that is set if `city` (0 based) is visited at `position`.
+    pub fn variable(&self, city: usize, position: usize) -> usize {
+        city * self.size() + position
+    }
+
+    /// Constraint penalty large enough that no constraint violation can pay off.
+    pub fn default_penalty(&self, b: f64) -> f64 {
+        (self.size() as f64) * b * self.max_distance
+    }
+
+    /// Tight lower bound of the penalty, the weighted length of the longest edge.
+    pub fn min_penalty(&self, b: f64) -> f64 {
+        b * self.max_distance
+    }
+
+    fn distance(&self, from: usize, to: usize) -> f64 {
+        self.matrix.get(from - 1, to - 1)
+    }
+
+    /// Adds `a * (sum_j x_{i,j} - 1)^2` for every city `i`.
+    pub fn add_row_constraints(&self, coo: &mut COOrdinate, a: f64) {
+        for city in 0..self.size() {
+            let row = (0..self.size())
+                .map(|position| self.variable(city, position))
+                .collect::<Vec<usize>>();
+            add_one_hot(coo, &row, a);
+        }
+    }
+
+    /// Adds `a * (sum_i x_{i,j} - 1)^2` for every position `j`.
+    pub fn add_column_constraints(&self, coo: &mut COOrdinate, a: f64) {
+        for position in 0..self.size() {
+            let column = (0..self.size())
+                .map(|city| self.variable(city, position))
+                .collect::<Vec<usize>>();
+            add_one_hot(coo, &column, a);
+        }
+    }
+
+    /// Adds `b * d(u, i) * x_{u,j} * x_{i,j+1}` for all consecutive positions and,
+    /// with a fixed depot, the edges from the depot to the first and from the last city.
+    pub fn add_objective(&self, coo: &mut COOrdinate, b: f64) {
+        let size = self.size();
+        for u in 0..size {
+            for i in 0..size {
+                if u == i {
+                    continue;
+                }
+                let distance = self.distance(self.cities[u], self.cities[i]);
+                for j in 0..size.saturating_sub(1) {
+                    coo.add(self.variable(u, j), self.variable(i, j + 1), b * distance);
+                }
+            }
+        }
+
+        if self.formulation == QuboFormulation::DepotFixed && size > 0 {
+            for (city, id) in self.cities.iter().enumerate() {
+                let first = self.variable(city, 0);
+                let last = self.variable(city, size - 1);
+                coo.add(first, first, b * self.distance(self.depot, *id));
+                coo.add(last, last, b * self.distance(*id, self.depot));
+            }
+        }
+    }
+
+    pub fn build(&self, a: f64, b: f64) -> COOrdinate {
+        let mut coo = COOrdinate::new(self.variables());
+        self.add_row_constraints(&mut coo, a);
+        self.add_column_constraints(&mut coo, a);
+        self.add_objective(&mut coo, b);
+        coo
+    }
+
+    /// Length of the closed tour over node ids.
+    pub fn tour_length(&self, tour: &[usize]) -> f64 {
+        self.matrix
+            .tour_length(&tour.iter().map(|id| id - 1).collect::<Vec<usize>>())
+    }
+
+    /// Assignment that visits the cities in the order of `tour`.
+    ///
+    /// With a fixed depot the tour has to start with the depot.
+    pub fn encode(&self, tour: &[usize]) -> Vec<u8> {
+        let mut sample = vec![0u8; self.variables()];
+        let skip = match self.formulation {
+            QuboFormulation::Full => 0,
+            QuboFormulation::DepotFixed => 1,
+        };
+        for (position, id) in tour.iter().skip(skip).enumerate() {
+            let city = self.cities.iter().position(|c| c == id).unwrap();
+            sample[self.variable(city, position)] = 1;
+        }
+        sample
+    }
+
+    /// Node ids whose order is encoded, with a fixed depot the depot is excluded.
+    pub fn cities(&self) -> &[usize] {
+        &self.cities
+    }
+
+    pub fn depot(&self) -> usize {
+        self.depot
+    }
+
+    pub fn formulation(&self) -> QuboFormulation {
+        self.formulation
+    }
+
+    /// Counts the cities and positions whose one-hot constraint is violated.
+    pub fn violations(&self, sample: &[u8]) -> (usize, usize) {
+        let size = self.size();
+        let rows = (0..size)
+            .filter(|city| {
+                (0..size)
+                    .filter(|position| sample[self.variable(*city, *position)] == 1)
+                    .count()
+                    != 1
+            })
+            .count();
+        let columns = (0..size)
+            .filter(|position| {
+                (0..size)
+                    .filter(|city| sample[self.variable(*city, *position)] == 1)
+                    .count()
+                    != 1
+            })
+            .count();
+        (rows, columns)
+    }
+
+    /// Whether every city is visited at exactly one position and every position is used once.
+    pub fn is_feasible(&self, sample: &[u8]) -> bool {
+        self.violations(sample) == (0, 0)
+    }
+
+    /// Maps an assignment of the variables back to a tour over the node ids.
+    ///
+    /// Infeasible samples are repaired: cities are greedily kept at one of their
+    /// sampled positions, the remaining ones are inserted where they are cheapest
+    /// and the tour is improved with 2-opt and Or-opt.
+    pub fn decode(&self, sample: &[u8]) -> DecodedTour {
+        let size = self.size();
+        let (row_violations, column_violations) = self.violations(sample);
+        let repaired = row_violations > 0 || column_violations > 0;
+
+        let set_positions = (0..size)
+            .map(|city| {
+                (0..size)
+                    .filter(|position| sample[self.variable(city, *position)] == 1)
+                    .count()
+            })
+            .collect::<Vec<usize>>();
+
+        // every position keeps the sampled city that has the fewest alternatives
+        let mut assigned = vec![false; size];
+        let mut tour = Vec::with_capacity(size + 1);
+        if self.formulation == QuboFormulation::DepotFixed {
+            tour.push(self.depot);
+        }
+        for position in 0..size {
+            let city = (0..size)
+                .filter(|city| !assigned[*city] && sample[self.variable(*city, position)] == 1)
+                .min_by_key(|city| set_positions[*city]);
+            if let Some(city) = city {
+                assigned[city] = true;
+                tour.push(self.cities[city]);
+            }
+        }
+
+        if !repaired {
+            return DecodedTour {
+                tour,
+                row_violations,
+                column_violations,
+                repaired,
+            };
+        }
+
+        for city in (0..size).filter(|city| !assigned[*city]) {
+            let id = self.cities[city];
+            if tour.is_empty() {
+                tour.push(id);
+                continue;
+            }
+            let insert_at = (0..tour.len())
+                .min_by(|a, b| {
+                    self.insertion_cost(&tour, *a, id)
+                        .total_cmp(&self.insertion_cost(&tour, *b, id))
+                })
+                .unwrap();
+            tour.insert(insert_at + 1, id);
+        }
+
+        let mut indices = tour.iter().map(|id| id - 1).collect::<Vec<usize>>();
+        let neighbours = self.matrix.neighbours(10);
+        self.matrix.improve_tour(&mut indices, &neighbours);
+        let mut tour = indices.iter().map(|i| i + 1).collect::<Vec<usize>>();
+        if let Some(position) = tour.iter().position(|id| *id == self.depot) {
+            tour.rotate_left(position);
+        }
+
+        DecodedTour {
+            tour,
+            row_violations,
+            column_violations,
+            repaired,
+        }
+    }
+
+    /// Decodes every sample and picks the shortest tour, preferring samples that
+    /// didn't need a repair on equal length.
+    pub fn best_tour(&self, sample_set: &SampleSet) -> (DecodedTour, SampleStatistics) {
+        let mut reads = 0;
+        let mut feasible_reads = 0;
+        let mut best_energy = f64::INFINITY;
+        let mut chosen: Option<(DecodedTour, f64, f64)> = None;
+
+        for (sample, energy, count) in sample_set.iter() {
+            reads += count;
+            best_energy = best_energy.min(energy);
+
+            let decoded = self.decode(sample);
+            if !decoded.repaired {
+                feasible_reads += count;
+            }
+            let length = self.tour_length(&decoded.tour);
+            let better = match &chosen {
+                None => true,
+                Some((best, best_length, _)) => {
+                    length < *best_length - 1e-9
+                        || (length <= *best_length + 1e-9 && best.repaired && !decoded.repaired)
+                }
+            };
+            if better {
+                chosen = Some((decoded, length, energy));
+            }
+        }
+
+        let (decoded, chosen_length, chosen_energy) = chosen.unwrap();
+        let statistics = SampleStatistics {
+            reads,
+            feasible_fraction: feasible_reads as f64 / reads.max(1) as f64,
+            best_energy,
+            chosen_energy,
+            chosen_length,
+            repaired: decoded.repaired,
+        };
+        (decoded, statistics)
+    }
+
+    /// Cost of inserting `id` after position `after` of the closed tour.
+    fn insertion_cost(&self, tour: &[usize], after: usize, id: usize) -> f64 {
+        let from = tour[after];
+        let to = tour[(after + 1) % tour.len()];
+        self.distance(from, id) + self.distance(id, to) - self.distance(from, to)
+    }
+}
+
+/// Largest TSP, counting the depot, whose QUBO fits into `qubits` variables.
+pub fn max_tsp_nodes(qubits: usize, formulation: QuboFormulation) -> usize {
+    let mut size = 0;
+    while (size + 1) * (size + 1) <= qubits {
+        size += 1;
+    }
+    match formulation {
+        QuboFormulation::Full => size,
+        QuboFormulation::DepotFixed => size + 1,
+    }
+}
+
+/// Adds the penalty `a * (sum x - 1)^2` that is zero iff exactly one variable is set.
+fn add_one_hot(coo: &mut COOrdinate, variables: &[usize], a: f64) {
+    for (k, x) in variables.iter().enumerate() {
+        coo.add(*x, *x, -a);
+        for y in &variables[k + 1..] {
+            coo.add(*x, *y, 2f64 * a);
+        }
+    }
+    coo.add_offset(a);
+}
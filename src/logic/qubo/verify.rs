@@ -0,0 +1,197 @@
+use super::{ground_states, GroundStates, QuboFormulation, TspQuboBuilder};
+
+/// Outcome of checking a TSP QUBO against brute force tour lengths.
+#[derive(Debug, Clone)]
+pub struct QuboVerification {
+    pub penalty: f64,
+    pub objective_weight: f64,
+    /// Number of permutations that were checked.
+    pub tours: usize,
+    pub optimal_length: f64,
+    /// Energy minus weighted tour length, the same for every feasible assignment.
+    pub constant: f64,
+    /// Number of ground states if the QUBO was small enough to be enumerated.
+    pub ground_states: Option<u64>,
+    pub mismatches: Vec<String>,
+}
+
+/// All orderings of `items`.
+fn permutations(items: &[usize]) -> Vec<Vec<usize>> {
+    if items.len() <= 1 {
+        return vec![items.to_vec()];
+    }
+    let mut result = vec![];
+    for (k, first) in items.iter().enumerate() {
+        let mut rest = items.to_vec();
+        rest.remove(k);
+        for mut tail in permutations(&rest) {
+            tail.insert(0, *first);
+            result.push(tail);
+        }
+    }
+    result
+}
+
+/// Checks that every tour has energy `b * length + constant` and, if the QUBO has
+/// at most `max_variables` variables, that its ground states are exactly optimal tours.
+pub fn verify_tsp_qubo(
+    builder: &TspQuboBuilder,
+    a: f64,
+    b: f64,
+    max_variables: usize,
+) -> QuboVerification {
+    let coo = builder.build(a, b);
+    let tolerance = 1e-6 * a.abs().max(1f64);
+    let mut mismatches = vec![];
+
+    let tours = permutations(builder.cities())
+        .into_iter()
+        .map(|tour| match builder.formulation() {
+            QuboFormulation::Full => tour,
+            QuboFormulation::DepotFixed => std::iter::once(builder.depot()).chain(tour).collect(),
+        })
+        .collect::<Vec<Vec<usize>>>();
+
+    let mut constant = None;
+    let mut optimal_length = f64::INFINITY;
+    let mut optimal_energy = f64::INFINITY;
+    for tour in &tours {
+        let length = builder.tour_length(tour);
+        let energy = coo.energy(&builder.encode(tour));
+        let difference = energy - b * length;
+        optimal_length = optimal_length.min(length);
+        optimal_energy = optimal_energy.min(energy);
+
+        match constant {
+            None => constant = Some(difference),
+            Some(constant) if (difference - constant).abs() > tolerance => {
+                mismatches.push(format!(
+                    "tour {tour:?} with length {length} has energy {energy}, expected {}",
+                    b * length + constant
+                ));
+            }
+            _ => {}
+        }
+    }
+    let constant = constant.unwrap_or(0f64);
+    if (constant + coo.offset()).abs() > tolerance {
+        mismatches.push(format!(
+            "energy of tours is shifted by {constant}, the QUBO offset is {}",
+            coo.offset()
+        ));
+    }
+
+    let ground_state_count = if coo.variables() <= max_variables {
+        let GroundStates {
+            energy: ground_energy,
+            count,
+            states,
+        } = ground_states(&coo);
+        if ground_energy < optimal_energy - tolerance {
+            mismatches.push(format!(
+                "ground state energy {ground_energy} is below the optimal tour energy {optimal_energy}"
+            ));
+        }
+        for state in &states {
+            let (rows, columns) = builder.violations(state);
+            if rows > 0 || columns > 0 {
+                mismatches.push(format!(
+                    "ground state violates {rows} row and {columns} column constraints"
+                ));
+                continue;
+            }
+            let tour = builder.decode(state).tour;
+            let length = builder.tour_length(&tour);
+            if length > optimal_length + tolerance {
+                mismatches.push(format!(
+                    "ground state tour {tour:?} has length {length}, the optimum is {optimal_length}"
+                ));
+            }
+        }
+        Some(count)
+    } else {
+        None
+    };
+
+    QuboVerification {
+        penalty: a,
+        objective_weight: b,
+        tours: tours.len(),
+        optimal_length,
+        constant,
+        ground_states: ground_state_count,
+        mismatches,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tspf::TspBuilder;
+
+    use super::*;
+
+    /// Depot 1 and three customers on the corners of a 4 by 3 rectangle, the optimal
+    /// tours go around it with length 14.
+    fn builder(formulation: QuboFormulation) -> TspQuboBuilder {
+        let tsp = TspBuilder::parse_str(
+            "NAME: rectangle
+TYPE: CVRP
+DIMENSION: 4
+CAPACITY: 10
+EDGE_WEIGHT_TYPE: EUC_2D
+NODE_COORD_SECTION:
+1 0 0
+2 0 3
+3 4 3
+4 4 0
+DEMAND_SECTION:
+1 0
+2 1
+3 1
+4 1
+DEPOT_SECTION:
+1
+-1
+EOF
+",
+        )
+        .unwrap();
+        TspQuboBuilder::new(&tsp, formulation)
+    }
+
+    #[test]
+    fn depot_fixed_ground_states_are_the_optimal_tours() {
+        let builder = builder(QuboFormulation::DepotFixed);
+        let verification = verify_tsp_qubo(&builder, builder.default_penalty(1.0), 1.0, 9);
+        assert!(verification.mismatches.is_empty(), "{verification:?}");
+        assert_eq!(verification.tours, 6);
+        assert!((verification.optimal_length - 14.0).abs() < 1e-9);
+        // both directions around the rectangle
+        assert_eq!(verification.ground_states, Some(2));
+    }
+
+    #[test]
+    fn reports_the_open_path_of_the_full_formulation() {
+        // the full formulation leaves out the edge back to the first city, whose
+        // length 3, 4 or 5 depends on the tour
+        let builder = builder(QuboFormulation::Full);
+        let verification = verify_tsp_qubo(&builder, builder.default_penalty(1.0), 1.0, 16);
+        assert_eq!(verification.tours, 24);
+        assert!(!verification.mismatches.is_empty());
+    }
+
+    #[test]
+    fn reports_a_penalty_that_is_too_small() {
+        let builder = builder(QuboFormulation::DepotFixed);
+        let verification = verify_tsp_qubo(&builder, 0.0, 1.0, 9);
+        assert!(!verification.mismatches.is_empty());
+    }
+
+    #[test]
+    fn skips_the_ground_states_of_larger_qubos() {
+        let builder = builder(QuboFormulation::DepotFixed);
+        let verification = verify_tsp_qubo(&builder, builder.default_penalty(1.0), 1.0, 8);
+        assert!(verification.mismatches.is_empty(), "{verification:?}");
+        assert_eq!(verification.ground_states, None);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::logic::util;
+
+use super::{
+    super::error_code::ExitCode,
+    solver::reindex_vrp,
+    solvers::{Problem, SolvingOutput, SolvingTrait},
+};
+
+use bimap::BiMap;
+use std::{process::exit, time::SystemTime};
+use tspf::{Point, Tsp, TspKind};
+
+/// Builds a single TSP over all nodes whose capacity covers the whole demand,
+/// so every TSP solver treats it as one route.
+fn giant_tsp(problem: &Tsp) -> (Tsp, BiMap<usize, usize>) {
+    let (vrp, map) = reindex_vrp(problem);
+    let total_demand: f64 = vrp.demands().values().sum();
+
+    (
+        Tsp::from(
+            format!("{}_giant", vrp.name()),
+            vrp.kind(),
+            format!("{} - Giant tour", vrp.comment()),
+            vrp.dim(),
+            vrp.capacity().max(total_demand),
+            vrp.weight_kind(),
+            vrp.weight_format(),
+            vrp.edge_format().clone(),
+            vrp.coord_kind(),
+            vrp.disp_kind(),
+            vrp.node_coords()
+                .iter()
+                .map(|(id, p)| (*id, Point::new(*id, p.pos().to_vec())))
+                .collect(),
+            vrp.depots().clone(),
+            vrp.demands().clone(),
+            vrp.fixed_edges().to_vec(),
+            vrp.disp_coords().to_vec(),
+            vrp.edge_weights().to_vec(),
+            vec![],
+        ),
+        map,
+    )
+}
+
+/// Route-first, cluster-second pipeline.
+///
+/// Solves one giant TSP over all customers and cuts the tour into capacity
+/// feasible routes with the optimal Split procedure.
+pub struct RouteFirstSolver {
+    pub solving_strat: Box<dyn SolvingTrait>,
+    /// Directory the giant TSP is written to, it stays in memory without one.
+    pub build_dir: Option<String>,
+}
+
+impl SolvingTrait for RouteFirstSolver {
+    fn solve(&self, instance: &Problem, transform_only: Option<bool>) -> SolvingOutput {
+        let problem = &instance.tsp;
+        if problem.kind() != TspKind::Cvrp {
+            println!(
+                "Invalid TSPLIB instance type {}. (supported is CVRP)",
+                problem.kind().to_string().to_uppercase()
+            );
+            exit(ExitCode::WrongTspType as i32);
+        }
+
+        let depot = match problem.depots().iter().min() {
+            Some(depot) => *depot,
+            None => {
+                println!("The VRP-Instance has no depot");
+                exit(1)
+            }
+        };
+
+        println!("name: {}", problem.name());
+        println!("type: {}", problem.kind());
+
+        let start_time = SystemTime::now();
+        println!("start");
+
+        let (giant, map) = giant_tsp(problem);
+        let name = format!("{}_giant", instance.name);
+        let mut giant = Problem::new(giant, &name, self.build_dir.clone());
+        if self.build_dir.is_some() {
+            giant.write_instance();
+        }
+        let giant_path = giant.label();
+
+        println!("solve {giant_path} start");
+        let tours = self.solving_strat.solve(&giant, transform_only);
+        let after_solve_time = SystemTime::now()
+            .duration_since(start_time)
+            .unwrap()
+            .as_secs_f32();
+        println!("solve {giant_path} end: {after_solve_time}");
+
+        if let Some(true) = transform_only {
+            return SolvingOutput::new(vec![]);
+        }
+
+        let mut tour: Vec<usize> = tours
+            .output()
+            .concat()
+            .iter()
+            .map(|id| *map.get_by_right(id).unwrap())
+            .collect();
+
+        // start the giant tour at the depot and drop all depot visits
+        if let Some(position) = tour.iter().position(|id| *id == depot) {
+            tour.rotate_left(position);
+        }
+        let sequence: Vec<usize> = tour
+            .into_iter()
+            .filter(|id| !problem.depots().contains(id))
+            .collect();
+
+        let before_split_time = SystemTime::now();
+        let all_paths = SolvingOutput::new(util::split::split(problem, depot, &sequence));
+        let split_time = SystemTime::now()
+            .duration_since(before_split_time)
+            .unwrap()
+            .as_secs_f32();
+        println!(
+            "split into {} routes: {split_time}",
+            all_paths.output().len()
+        );
+
+        let finished = SystemTime::now()
+            .duration_since(start_time)
+            .unwrap()
+            .as_secs_f32();
+        println!("finished: {finished}");
+
+        let sol_length = util::tsp::calculate_solution_score(problem, all_paths.output());
+
+        println!("length: {sol_length}");
+
+        instance.write_solution(&all_paths);
+
+        all_paths
+    }
+}
@@ -1,23 +1,27 @@
-use crate::logic::{solvers::VRPTourWriter, util};
+use crate::logic::util::{self, log};
 
 use super::{
     super::error_code::ExitCode,
     clustering::{ClusterOutput, ClusteringTrait},
-    solvers::{SolvingOutput, SolvingTrait},
+    solvers::{Problem, SolvingOutput, SolvingTrait},
 };
 
 use bimap::BiMap;
 use std::{
     collections::HashMap,
-    fs::{self, File},
+    fs,
     io::Write,
-    path::Path,
     process::exit,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
     time::SystemTime,
 };
-use tspf::{Point, Tsp, TspBuilder, TspKind, TspSerializer};
+use tspf::{Point, Tsp, TspKind};
 
-fn reindex_vrp(vrp: &Tsp) -> (Tsp, BiMap<usize, usize>) {
+pub(crate) fn reindex_vrp(vrp: &Tsp) -> (Tsp, BiMap<usize, usize>) {
     let mut pts = vrp.node_coords().values().collect::<Vec<&Point>>();
 
     pts.sort_by_key(|point| point.id());
@@ -74,37 +78,39 @@ pub struct VrpSolver {
     pub cluster_strat: Box<dyn ClusteringTrait>,
     pub solving_strat: Box<dyn SolvingTrait>,
     pub build_dir: Option<String>,
+    /// Number of clusters solved at the same time.
+    pub workers: usize,
 }
 impl VrpSolver {
-    pub fn partial_cluster(
-        &self,
-        path: &str,
-        problem: &Tsp,
-    ) -> Vec<(File, String, BiMap<usize, usize>)> {
-        let file_name = Path::new(path).file_stem().unwrap().to_str().unwrap();
-
+    /// Clusters the problem into subproblems in the build dir. If there is one, their
+    /// instances and reindex maps are written to it.
+    pub fn partial_cluster(&self, problem: &Problem) -> Vec<(Problem, BiMap<usize, usize>)> {
+        let prefix = log::cluster_prefix();
         let start_time = SystemTime::now();
-        let clusters = self.cluster_strat.cluster(problem);
-        println!("{:?}", clusters);
-        let vrps_raw = self.cluster_tsps(problem, clusters);
+        let clusters = self.cluster_strat.cluster(&problem.tsp);
+        println!("{prefix}{:?}", clusters);
+        let vrps_raw = self.cluster_tsps(&problem.tsp, clusters);
 
         let after_cluster_time = SystemTime::now()
             .duration_since(start_time)
             .unwrap()
             .as_secs_f32();
-        println!("clustered after: {after_cluster_time}");
+        println!("{prefix}clustered after: {after_cluster_time}");
 
-        let vrps: Vec<(usize, Tsp, BiMap<usize, usize>)> = vrps_raw
-            .iter()
+        let mut vrps: Vec<(Problem, BiMap<usize, usize>)> = vrps_raw
+            .into_iter()
             .map(|(i, tsp)| {
-                let mapped = reindex_vrp(tsp);
-                (*i, mapped.0, mapped.1)
+                let (vrp, map) = reindex_vrp(&tsp);
+                let name = format!("{}_{}", problem.name, i);
+                (Problem::new(vrp, &name, self.build_dir.clone()), map)
             })
             .collect();
 
-        let build_dir = self.build_dir();
+        let Some(build_dir) = &self.build_dir else {
+            return vrps;
+        };
 
-        match fs::create_dir_all(&build_dir) {
+        match fs::create_dir_all(build_dir) {
             Ok(_) => {}
             Err(e) => {
                 println!("Something went wrong creating dirs: \n{e}");
@@ -113,7 +119,7 @@ impl VrpSolver {
         };
 
         // serialize reindex map
-        let map_file_path = format!("{}/{}.map", build_dir, file_name);
+        let map_file_path = format!("{}/{}.map", build_dir, problem.name);
         let mut map_file = match std::fs::File::create(&map_file_path) {
             Ok(file) => file,
             Err(e) => {
@@ -121,7 +127,7 @@ impl VrpSolver {
                 exit(1)
             }
         };
-        for (_, _, map) in &vrps {
+        for (_, map) in &vrps {
             let map = map
                 .iter()
                 .filter(|(i, _)| **i != 1usize)
@@ -131,21 +137,10 @@ impl VrpSolver {
             write!(map_file, "{map}\n-1\n").unwrap();
         }
 
-        vrps.iter()
-            .map(|(i, vrp, map)| {
-                let (file, path) = match TspSerializer::serialize_file(
-                    vrp,
-                    format!("{}/{}_{}.vrp", build_dir, &file_name, i),
-                ) {
-                    Err(err) => {
-                        println!("{}", err);
-                        exit(1)
-                    }
-                    Ok(file) => file,
-                };
-                (file, path, map.clone())
-            })
-            .collect()
+        for (vrp, _) in vrps.iter_mut() {
+            vrp.write_instance();
+        }
+        vrps
     }
     fn cluster_tsps(&self, problem: &Tsp, clusters: ClusterOutput) -> Vec<(usize, Tsp)> {
         let mut tsps: Vec<(usize, Tsp)> = Vec::new();
@@ -216,24 +211,80 @@ impl VrpSolver {
         }
         tsps
     }
-    fn build_dir(&self) -> String {
-        if let Some(dir) = &self.build_dir {
-            dir.clone()
-        } else {
-            String::from("./.vrp")
+    /// Solves the clusters with up to `workers` threads, the outputs keep the cluster order.
+    fn solve_clusters(
+        &self,
+        vrps: &[(Problem, BiMap<usize, usize>)],
+        transform_only: Option<bool>,
+    ) -> Vec<SolvingOutput> {
+        if self.workers <= 1 || vrps.len() <= 1 {
+            return vrps
+                .iter()
+                .map(|(vrp, map)| self.solve_cluster(vrp, map, transform_only))
+                .collect();
         }
+
+        let next = AtomicUsize::new(0);
+        let outputs = Mutex::new(vrps.iter().map(|_| None).collect::<Vec<_>>());
+        thread::scope(|scope| {
+            for _ in 0..self.workers.min(vrps.len()) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((vrp, map)) = vrps.get(i) else {
+                        break;
+                    };
+                    let output = log::with_cluster(&vrp.name, || {
+                        self.solve_cluster(vrp, map, transform_only)
+                    });
+                    outputs.lock().unwrap()[i] = Some(output);
+                });
+            }
+        });
+        outputs
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|output| output.unwrap())
+            .collect()
+    }
+
+    /// Solves the cluster and maps its tours back to the ids of the instance.
+    fn solve_cluster(
+        &self,
+        vrp: &Problem,
+        map: &BiMap<usize, usize>,
+        transform_only: Option<bool>,
+    ) -> SolvingOutput {
+        let prefix = log::cluster_prefix();
+        let path = vrp.label();
+        let before_solve_time = SystemTime::now();
+        println!("{prefix}solve {path} start");
+
+        let paths = self.solving_strat.solve(vrp, transform_only);
+
+        let after_solve_time = SystemTime::now()
+            .duration_since(before_solve_time)
+            .unwrap()
+            .as_secs_f32();
+        println!("{prefix}solve {path} end: {after_solve_time}");
+
+        let paths = paths
+            .output()
+            .iter()
+            .map(|path| {
+                path.iter()
+                    .map(|id| *map.get_by_right(id).unwrap())
+                    .collect()
+            })
+            .collect();
+
+        SolvingOutput::new(paths)
     }
 }
 
 impl SolvingTrait for VrpSolver {
-    fn solve(&self, path: &str, transform_only: Option<bool>) -> SolvingOutput {
-        let problem = match TspBuilder::parse_path(path) {
-            Ok(instance) => instance,
-            Err(e) => {
-                println!("Problems reading the VRP-Instance: {}", e);
-                exit(ExitCode::ReadProblems as i32);
-            }
-        };
+    fn solve(&self, instance: &Problem, transform_only: Option<bool>) -> SolvingOutput {
+        let problem = &instance.tsp;
         if problem.kind() != TspKind::Cvrp {
             println!(
                 "Invalid TSPLIB instance type {}. (supported is CVRP)",
@@ -242,45 +293,23 @@ impl SolvingTrait for VrpSolver {
             exit(ExitCode::WrongTspType as i32);
         }
 
-        println!("name: {}", problem.name());
-        println!("type: {}", problem.kind());
+        let prefix = log::cluster_prefix();
+        println!("{prefix}name: {}", problem.name());
+        println!("{prefix}type: {}", problem.kind());
 
         let start_time = SystemTime::now();
-        println!("start");
-        let vrps = self.partial_cluster(path, &problem);
+        println!("{prefix}start");
+        let vrps = self.partial_cluster(instance);
 
         let solver_start = SystemTime::now()
             .duration_since(start_time)
             .unwrap()
             .as_secs_f32();
-        println!("start solving clustered vrps: {solver_start}");
+        println!("{prefix}start solving clustered vrps: {solver_start}");
 
-        let all_paths = vrps
-            .iter()
-            .map(|(_file, path, map)| {
-                let before_solve_time = SystemTime::now();
-                println!("solve {path} start");
-
-                let paths = self.solving_strat.solve(&path[..], transform_only);
-
-                let after_solve_time = SystemTime::now()
-                    .duration_since(before_solve_time)
-                    .unwrap()
-                    .as_secs_f32();
-                println!("solve {path} end: {after_solve_time}");
-
-                let paths = paths
-                    .output()
-                    .iter()
-                    .map(|path| {
-                        path.iter()
-                            .map(|id| *map.get_by_right(id).unwrap())
-                            .collect()
-                    })
-                    .collect();
-
-                SolvingOutput::new(paths)
-            })
+        let all_paths = self
+            .solve_clusters(&vrps, transform_only)
+            .into_iter()
             .reduce(|paths, new_paths| {
                 let combined_paths: [Vec<Vec<usize>>; 2] = [paths.into(), new_paths.into()];
                 SolvingOutput::new(combined_paths.concat())
@@ -294,25 +323,13 @@ impl SolvingTrait for VrpSolver {
             .duration_since(start_time)
             .unwrap()
             .as_secs_f32();
-        println!("finished: {finished}");
-
-        let sol_length = util::tsp::calculate_solution_score(&problem, &all_paths.output());
-
-        println!("length: {sol_length}");
+        println!("{prefix}finished: {finished}");
 
-        let file_dir = Path::new(path).parent().unwrap().to_str().unwrap();
-        let file_name = Path::new(path).file_stem().unwrap().to_str().unwrap();
+        let sol_length = util::tsp::calculate_solution_score(problem, all_paths.output());
 
-        let mut file = match std::fs::File::create(format!("{}/{}.sol", file_dir, file_name)) {
-            Ok(file) => file,
-            Err(e) => {
-                println!("Problem opening solution file {e}");
-                exit(1)
-            }
-        };
+        println!("{prefix}length: {sol_length}");
 
-        println!("writing tours to file {file_dir}/{file_name}.sol");
-        (&problem, &all_paths).write_tours(&mut file).unwrap();
+        instance.write_solution(&all_paths);
 
         all_paths
     }
@@ -7,6 +7,8 @@ use tspf::Tsp;
 
 use crate::logic::util;
 
+use super::Problem;
+
 pub struct SolvingOutput(Vec<Vec<usize>>);
 
 impl SolvingOutput {
@@ -63,6 +65,7 @@ impl VRPTourWriter for (&Tsp, &SolvingOutput) {
     }
 }
 
-pub trait SolvingTrait {
-    fn solve(&self, path: &str, transform_only: Option<bool>) -> SolvingOutput;
+/// A solver of (sub)problems, shared between the workers solving clusters in parallel.
+pub trait SolvingTrait: Send + Sync {
+    fn solve(&self, problem: &Problem, transform_only: Option<bool>) -> SolvingOutput;
 }
@@ -0,0 +1,408 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::{Path, PathBuf},
+    process::{exit, Command, Stdio},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::logic::{
+    qubo::{COOrdinate, QuboExportFormat, QuboSampler, SampleSet},
+    util::log,
+};
+
+use super::{Problem, SolvingOutput, SolvingTrait};
+
+/// Version of the external solver protocol described in `EXTERNAL_SOLVERS.md`.
+pub const EXTERNAL_PROTOCOL_VERSION: u32 = 1;
+
+/// Config file that is read when no other one is given and it exists.
+pub const DEFAULT_EXTERNAL_CONFIG: &str = "./external_solvers.toml";
+
+/// How the request is handed to the external solver.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RequestChannel {
+    /// JSON request written to the standard input.
+    #[default]
+    Stdin,
+    /// JSON request written next to the instance, its path is appended as last argument.
+    File,
+}
+
+/// Command line of an external solver and the options passed in every request.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExternalSolverConfig {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Directory the command is run in, relative to the current directory.
+    pub working_dir: Option<String>,
+    #[serde(default)]
+    pub input: RequestChannel,
+    /// Seconds the solver should stop after, it is responsible to honour it.
+    pub time_limit: Option<f64>,
+    #[serde(default)]
+    pub options: Map<String, Value>,
+}
+
+/// Named external solvers from the config file on top of the built in wrappers.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExternalSolvers {
+    #[serde(default)]
+    solvers: BTreeMap<String, ExternalSolverConfig>,
+    /// Directory of the python wrappers of the built in solvers.
+    #[serde(skip)]
+    python_dir: String,
+}
+
+/// What the external solver is asked for.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RequestKind {
+    /// Tours of the instance as node ids.
+    Tour,
+    /// A sample set of the QUBO.
+    Samples,
+}
+
+/// QUBO files of a `samples` request keyed by their extension.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExternalQubo {
+    pub variables: usize,
+    pub offset: f64,
+    pub files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExternalRequest {
+    pub protocol: u32,
+    pub kind: RequestKind,
+    /// Absolute path of the tsplib instance.
+    pub instance: Option<String>,
+    pub qubo: Option<ExternalQubo>,
+    pub options: Map<String, Value>,
+    pub time_limit: Option<f64>,
+    /// Absolute path the solver writes its response to.
+    pub response_file: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExternalResponse {
+    #[serde(default)]
+    pub tours: Vec<Vec<usize>>,
+    pub sample_set: Option<SampleSet>,
+    #[serde(default)]
+    pub metadata: Map<String, Value>,
+    /// Set by the solver if it couldn't solve the request.
+    pub error: Option<String>,
+}
+
+impl ExternalSolvers {
+    /// Reads the config file, the default one only if it exists.
+    pub fn load(path: &Option<String>, python_dir: &str) -> Self {
+        let path = match path {
+            Some(path) => path.clone(),
+            None if Path::new(DEFAULT_EXTERNAL_CONFIG).exists() => {
+                String::from(DEFAULT_EXTERNAL_CONFIG)
+            }
+            None => {
+                return ExternalSolvers {
+                    solvers: BTreeMap::new(),
+                    python_dir: python_dir.to_string(),
+                }
+            }
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                println!("Problem opening external solver config {path}: {e}");
+                exit(1)
+            }
+        };
+        match toml::from_str::<ExternalSolvers>(&content) {
+            Ok(solvers) => ExternalSolvers {
+                python_dir: python_dir.to_string(),
+                ..solvers
+            },
+            Err(e) => {
+                println!("Problem parsing external solver config {path}: {e}");
+                exit(1)
+            }
+        }
+    }
+
+    /// Solver configured as `name`, falling back to the built in wrappers.
+    pub fn get(&self, name: &str) -> ExternalSolver {
+        let config = self
+            .solvers
+            .get(name)
+            .cloned()
+            .or_else(|| builtin_solver(name, &self.python_dir));
+        match config {
+            Some(config) => ExternalSolver {
+                name: name.to_string(),
+                config,
+            },
+            None => {
+                let mut names = self.solvers.keys().cloned().collect::<Vec<String>>();
+                names.extend(BUILTIN_SOLVERS.iter().map(|name| name.to_string()));
+                names.sort();
+                names.dedup();
+                println!(
+                    "Unknown external solver {name}, configured are: {}",
+                    names.join(", ")
+                );
+                exit(1)
+            }
+        }
+    }
+}
+
+const BUILTIN_SOLVERS: [&str; 3] = ["dwave", "lkh", "mock"];
+
+/// The python wrappers shipped with the repository, run from their directory.
+fn builtin_solver(name: &str, python_dir: &str) -> Option<ExternalSolverConfig> {
+    let (command, args, working_dir) = match name {
+        "dwave" => (
+            "poetry",
+            vec!["run", "python", "src/main.py", "--request"],
+            "qubo_solver",
+        ),
+        "lkh" => (
+            "poetry",
+            vec!["run", "python", "src/main.py", "--request"],
+            "lkh-interface",
+        ),
+        "mock" => ("python3", vec!["main.py"], "mock_solver"),
+        _ => return None,
+    };
+    Some(ExternalSolverConfig {
+        command: command.to_string(),
+        args: args.into_iter().map(String::from).collect(),
+        working_dir: Some(format!("{python_dir}/{working_dir}")),
+        input: RequestChannel::File,
+        time_limit: None,
+        options: Map::new(),
+    })
+}
+
+/// Runs a configured command that speaks the external solver protocol.
+#[derive(Debug, Clone)]
+pub struct ExternalSolver {
+    pub name: String,
+    pub config: ExternalSolverConfig,
+}
+
+impl ExternalSolver {
+    /// Sets a request option, overriding the configured value.
+    pub fn with_option(mut self, key: &str, value: Value) -> Self {
+        self.config.options.insert(key.to_string(), value);
+        self
+    }
+
+    fn request(
+        &self,
+        kind: RequestKind,
+        instance: Option<String>,
+        qubo: Option<ExternalQubo>,
+        file_name: &str,
+    ) -> ExternalRequest {
+        ExternalRequest {
+            protocol: EXTERNAL_PROTOCOL_VERSION,
+            kind,
+            instance,
+            qubo,
+            options: self.config.options.clone(),
+            time_limit: self.config.time_limit,
+            response_file: format!("{}{}.response.json", file_name, self.name),
+        }
+    }
+
+    /// Writes `request` to `{file_name}{name}.request.json` and returns the path.
+    fn write_request(&self, request: &ExternalRequest, file_name: &str) -> String {
+        let request_file = format!("{}{}.request.json", file_name, self.name);
+        let content = serde_json::to_string_pretty(request).unwrap();
+        if let Err(e) = fs::write(&request_file, content) {
+            println!("Problem writing request file {request_file}: {e}");
+            exit(1)
+        }
+        request_file
+    }
+
+    /// Runs the solver on `request`, echoing its output, and reads the response.
+    pub fn run(&self, request: &ExternalRequest, file_name: &str) -> ExternalResponse {
+        let content = serde_json::to_string_pretty(request).unwrap();
+        let mut command = Command::new(&self.config.command);
+        command.args(&self.config.args).stdout(Stdio::piped());
+        if let Some(working_dir) = &self.config.working_dir {
+            command.current_dir(working_dir);
+        }
+        match self.config.input {
+            RequestChannel::Stdin => {
+                command.stdin(Stdio::piped());
+            }
+            RequestChannel::File => {
+                command.arg(self.write_request(request, file_name));
+            }
+        }
+
+        // a response left by an earlier run must not be read as this one's
+        if let Err(e) = fs::remove_file(&request.response_file) {
+            if e.kind() != ErrorKind::NotFound {
+                println!(
+                    "Problem removing old response file {}: {e}",
+                    request.response_file
+                );
+                exit(1)
+            }
+        }
+
+        let mut cmd = match command.spawn() {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                println!(
+                    "Problem starting external solver {} ({}): {e}",
+                    self.name, self.config.command
+                );
+                exit(1)
+            }
+        };
+
+        if let Some(mut stdin) = cmd.stdin.take() {
+            if let Err(e) = stdin.write_all(content.as_bytes()) {
+                println!(
+                    "Problem sending request to external solver {}: {e}",
+                    self.name
+                );
+                exit(1)
+            }
+        }
+
+        let prefix = log::cluster_prefix();
+        {
+            let stdout = cmd.stdout.as_mut().unwrap();
+            let stdout_reader = BufReader::new(stdout);
+            let stdout_lines = stdout_reader.lines();
+
+            for line in stdout_lines {
+                println!("{prefix}{}: {}", self.name, line.unwrap());
+            }
+        }
+
+        let status = cmd.wait().unwrap();
+        if !status.success() {
+            println!("External solver {} failed with {status}", self.name);
+            exit(1)
+        }
+
+        let response_file = &request.response_file;
+        let response = match fs::read_to_string(response_file) {
+            Ok(response) => response,
+            Err(e) => {
+                println!("Problem opening response file {response_file}: {e}");
+                exit(1)
+            }
+        };
+        let response: ExternalResponse = match serde_json::from_str(&response) {
+            Ok(response) => response,
+            Err(e) => {
+                println!("Problem parsing response file {response_file}: {e}");
+                exit(1)
+            }
+        };
+        if let Some(error) = &response.error {
+            println!(
+                "External solver {} couldn't solve the request: {error}",
+                self.name
+            );
+            exit(1)
+        }
+        if !response.metadata.is_empty() {
+            println!(
+                "{prefix}{}: metadata {}",
+                self.name,
+                Value::Object(response.metadata.clone())
+            );
+        }
+        response
+    }
+}
+
+/// Absolute path of `path` and the same path without its extension, ending with a dot.
+fn absolute_file_name(path: &str) -> (String, String) {
+    let abs_path = match fs::canonicalize(PathBuf::from(path)) {
+        Ok(abs_path) => abs_path,
+        Err(e) => {
+            println!("Problem resolving {path}: {e}");
+            exit(1)
+        }
+    };
+    let abs_path = abs_path.to_str().unwrap().to_string();
+    let file_name = abs_path
+        .split_inclusive('.')
+        .collect::<Vec<&str>>()
+        .split_last()
+        .unwrap()
+        .1
+        .iter()
+        .fold(String::from(""), |x, y| x + y);
+    (abs_path, file_name)
+}
+
+impl SolvingTrait for ExternalSolver {
+    fn solve(&self, problem: &Problem, transform_only: Option<bool>) -> SolvingOutput {
+        let (abs_path, file_name) = absolute_file_name(&problem.instance_file());
+        let request = self.request(RequestKind::Tour, Some(abs_path), None, &file_name);
+        if let Some(true) = transform_only {
+            self.write_request(&request, &file_name);
+            return SolvingOutput::new(vec![vec![]]);
+        }
+        let response = self.run(&request, &file_name);
+        if response.tours.is_empty() {
+            println!(
+                "External solver {} returned no tours for {}",
+                self.name,
+                problem.label()
+            );
+            exit(1)
+        }
+        SolvingOutput::new(response.tours)
+    }
+}
+
+impl QuboSampler for ExternalSolver {
+    fn sample(&self, coo: &COOrdinate, file_name: &str) -> SampleSet {
+        let files = [QuboExportFormat::Coordinate, QuboExportFormat::DwaveLp]
+            .iter()
+            .map(|format| {
+                (
+                    format.extension().to_string(),
+                    format!("{}{}", file_name, format.extension()),
+                )
+            })
+            .collect();
+        let qubo = ExternalQubo {
+            variables: coo.variables(),
+            offset: coo.offset(),
+            files,
+        };
+        let request = self.request(RequestKind::Samples, None, Some(qubo), file_name);
+        match self.run(&request, file_name).sample_set {
+            Some(sample_set) => sample_set,
+            None => {
+                println!("External solver {} returned no sample set", self.name);
+                exit(1)
+            }
+        }
+    }
+
+    fn reads_files(&self) -> bool {
+        true
+    }
+}
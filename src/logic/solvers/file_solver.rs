@@ -1,17 +1,15 @@
 use tspf::TspBuilder;
 
-use super::{SolvingOutput, SolvingTrait};
-use std::{path::Path, process::exit};
+use super::{Problem, SolvingOutput, SolvingTrait};
+use std::process::exit;
 
 pub struct FileSolver {
     pub solution_file_dir: String,
 }
 
 impl SolvingTrait for FileSolver {
-    fn solve(&self, path: &str, _transform_only: Option<bool>) -> SolvingOutput {
-        let file_name = Path::new(path).file_stem().unwrap().to_str().unwrap();
-
-        let file_path = format!("{}/{}.sol", self.solution_file_dir, file_name);
+    fn solve(&self, problem: &Problem, _transform_only: Option<bool>) -> SolvingOutput {
+        let file_path = format!("{}/{}.sol", self.solution_file_dir, problem.name);
 
         println!("file path: {}", file_path);
 
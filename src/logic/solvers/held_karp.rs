@@ -0,0 +1,155 @@
+use std::process::exit;
+
+use crate::logic::util::{log, tour::DistanceMatrix};
+
+use super::{Problem, SolvingOutput, SolvingTrait};
+
+/// Largest instance the dynamic program is run on, its tables take about 160 MB.
+pub const MAX_EXACT_NODES: usize = 20;
+
+/// Exact Held–Karp dynamic program for small TSP subproblems.
+///
+/// Needs `O(2^n * n)` memory, so instances with more than `max_nodes` nodes are refused,
+/// and never more than [`MAX_EXACT_NODES`].
+pub struct HeldKarpSolver {
+    pub max_nodes: usize,
+}
+
+/// Returns the optimal tour over matrix indices starting at `start` and its length.
+pub fn held_karp(matrix: &DistanceMatrix, start: usize) -> (Vec<usize>, f64) {
+    let n = matrix.len();
+    assert!(n <= MAX_EXACT_NODES, "{n} nodes are too many for Held-Karp");
+    if n <= 1 {
+        return ((0..n).collect(), 0f64);
+    }
+
+    // every node except the start gets a bit in the subset masks
+    let others = (0..n).filter(|i| *i != start).collect::<Vec<usize>>();
+    let m = others.len();
+    let subsets = 1usize << m;
+
+    // costs[mask * m + last]: shortest path from start visiting mask and ending in others[last]
+    let mut costs = vec![f64::INFINITY; subsets * m];
+    let mut parents = vec![usize::MAX; subsets * m];
+    for (last, node) in others.iter().enumerate() {
+        costs[(1 << last) * m + last] = matrix.get(start, *node);
+    }
+
+    for mask in 1..subsets {
+        for last in 0..m {
+            if mask & (1 << last) == 0 {
+                continue;
+            }
+            let cost = costs[mask * m + last];
+            if cost.is_infinite() {
+                continue;
+            }
+            for next in 0..m {
+                if mask & (1 << next) != 0 {
+                    continue;
+                }
+                let next_mask = mask | (1 << next);
+                let next_cost = cost + matrix.get(others[last], others[next]);
+                if next_cost < costs[next_mask * m + next] {
+                    costs[next_mask * m + next] = next_cost;
+                    parents[next_mask * m + next] = last;
+                }
+            }
+        }
+    }
+
+    let full = subsets - 1;
+    let (mut last, length) = (0..m)
+        .map(|last| {
+            (
+                last,
+                costs[full * m + last] + matrix.get(others[last], start),
+            )
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .unwrap();
+
+    let mut tour = Vec::with_capacity(n);
+    let mut mask = full;
+    while last != usize::MAX {
+        tour.push(others[last]);
+        let parent = parents[mask * m + last];
+        mask &= !(1 << last);
+        last = parent;
+    }
+    tour.push(start);
+    tour.reverse();
+
+    (tour, length)
+}
+
+impl SolvingTrait for HeldKarpSolver {
+    fn solve(&self, problem: &Problem, _transform_only: Option<bool>) -> SolvingOutput {
+        let tsp = &problem.tsp;
+        let path = problem.label();
+
+        let max_nodes = self.max_nodes.min(MAX_EXACT_NODES);
+        if tsp.node_coords().len() > max_nodes {
+            println!(
+                "{}Held-Karp Solver: {path} has {} nodes, only up to {max_nodes} are supported",
+                log::cluster_prefix(),
+                tsp.node_coords().len()
+            );
+            exit(1)
+        }
+
+        let matrix = DistanceMatrix::from(tsp);
+        let depot = tsp
+            .depots()
+            .iter()
+            .min()
+            .and_then(|depot| matrix.ids.iter().position(|id| id == depot))
+            .unwrap_or(0);
+
+        let (tour, length) = held_karp(&matrix, depot);
+        println!(
+            "{}Held-Karp Solver: optimal length of {path}: {length}",
+            log::cluster_prefix()
+        );
+
+        SolvingOutput::new(vec![tour.iter().map(|i| matrix.ids[*i]).collect()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 4 cities where going around `0 -> 1 -> 2 -> 3` costs 1 per step, the reverse
+    /// direction 2 and the diagonals 5, so the only optimal tour has length 4.
+    fn matrix() -> DistanceMatrix {
+        DistanceMatrix {
+            ids: vec![1, 2, 3, 4],
+            distances: vec![
+                vec![0.0, 1.0, 5.0, 2.0],
+                vec![2.0, 0.0, 1.0, 5.0],
+                vec![5.0, 2.0, 0.0, 1.0],
+                vec![1.0, 5.0, 2.0, 0.0],
+            ],
+        }
+    }
+
+    #[test]
+    fn finds_the_optimal_tour() {
+        assert_eq!(held_karp(&matrix(), 0), (vec![0, 1, 2, 3], 4.0));
+    }
+
+    #[test]
+    fn starts_the_tour_at_start() {
+        assert_eq!(held_karp(&matrix(), 2), (vec![2, 3, 0, 1], 4.0));
+    }
+
+    #[test]
+    fn solves_a_single_node() {
+        let matrix = DistanceMatrix {
+            ids: vec![1],
+            distances: vec![vec![0.0]],
+        };
+        assert_eq!(held_karp(&matrix, 0), (vec![0], 0.0));
+    }
+}
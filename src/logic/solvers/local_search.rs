@@ -0,0 +1,62 @@
+use std::time::SystemTime;
+
+use crate::logic::util::{log, tour::DistanceMatrix};
+
+use super::{Problem, SolvingOutput, SolvingTrait};
+
+pub enum TourConstruction {
+    NearestNeighbour,
+    Greedy,
+}
+
+/// In-process TSP solver combining a construction heuristic with 2-opt and Or-opt
+/// local search on neighbour lists.
+pub struct LocalSearchSolver {
+    pub construction: TourConstruction,
+    /// Size of the neighbour lists used to restrict candidate moves.
+    pub neighbours: usize,
+}
+
+impl SolvingTrait for LocalSearchSolver {
+    fn solve(&self, problem: &Problem, _transform_only: Option<bool>) -> SolvingOutput {
+        let tsp = &problem.tsp;
+
+        let before_solve_time = SystemTime::now();
+        let matrix = DistanceMatrix::from(tsp);
+        if matrix.is_empty() {
+            return SolvingOutput::new(vec![]);
+        }
+
+        let depot = tsp
+            .depots()
+            .iter()
+            .min()
+            .and_then(|depot| matrix.ids.iter().position(|id| id == depot))
+            .unwrap_or(0);
+
+        let mut tour = match self.construction {
+            TourConstruction::NearestNeighbour => matrix.nearest_neighbour_tour(depot),
+            TourConstruction::Greedy => matrix.greedy_tour(),
+        };
+        let constructed = matrix.tour_length(&tour);
+
+        let neighbours = matrix.neighbours(self.neighbours);
+        matrix.improve_tour(&mut tour, &neighbours);
+        let improved = matrix.tour_length(&tour);
+
+        if let Some(position) = tour.iter().position(|node| *node == depot) {
+            tour.rotate_left(position);
+        }
+
+        let solve_time = SystemTime::now()
+            .duration_since(before_solve_time)
+            .unwrap()
+            .as_secs_f32();
+        println!(
+            "{}Local Search Solver: constructed {constructed}, improved {improved} in {solve_time}",
+            log::cluster_prefix()
+        );
+
+        SolvingOutput::new(vec![tour.iter().map(|i| matrix.ids[*i]).collect()])
+    }
+}
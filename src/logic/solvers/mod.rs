@@ -1,13 +1,21 @@
 mod common;
 mod dummy;
+mod external;
 mod file_solver;
+mod held_karp;
 mod hybrid_tsp_solver;
 mod lkh;
+mod local_search;
+mod problem;
 mod rust_vrp;
 
 pub use common::*;
 pub use dummy::*;
+pub use external::*;
 pub use file_solver::*;
+pub use held_karp::*;
 pub use hybrid_tsp_solver::*;
 pub use lkh::*;
+pub use local_search::*;
+pub use problem::*;
 pub use rust_vrp::*;
@@ -0,0 +1,135 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{self, exit},
+};
+
+use tspf::{Tsp, TspBuilder, TspSerializer};
+
+use crate::{error_code::ExitCode, logic::util::log};
+
+use super::{SolvingOutput, VRPTourWriter};
+
+/// A (sub)problem for a [`SolvingTrait`](super::SolvingTrait), its instance is held in memory.
+///
+/// Files are only artifacts of a problem: they are written to its work dir if it has one,
+/// solvers running external programs fall back to a temporary dir.
+pub struct Problem {
+    pub tsp: Tsp,
+    /// Name the artifacts are written under, the file stem of the instance.
+    pub name: String,
+    pub work_dir: Option<String>,
+    /// Instance file the problem was read from or written to.
+    pub source: Option<String>,
+}
+
+impl Problem {
+    pub fn new(tsp: Tsp, name: &str, work_dir: Option<String>) -> Self {
+        Problem {
+            tsp,
+            name: name.to_string(),
+            work_dir,
+            source: None,
+        }
+    }
+
+    /// Reads the instance at `path`, its artifacts are written next to it.
+    pub fn read(path: &str) -> Self {
+        let tsp = match TspBuilder::parse_path(path) {
+            Ok(tsp) => tsp,
+            Err(e) => {
+                println!("Problems reading the VRP-Instance: {}", e);
+                exit(ExitCode::ReadProblems as i32);
+            }
+        };
+        let file = Path::new(path);
+        let work_dir = match file.parent().and_then(Path::to_str) {
+            Some("") | None => String::from("."),
+            Some(dir) => dir.to_string(),
+        };
+        Problem {
+            tsp,
+            name: file.file_stem().unwrap().to_str().unwrap().to_string(),
+            work_dir: Some(work_dir),
+            source: Some(path.to_string()),
+        }
+    }
+
+    /// The instance file if there is one, otherwise the name.
+    pub fn label(&self) -> &str {
+        self.source.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Absolute path prefix `{dir}/{name}.` of the artifacts in the work dir. Without
+    /// one there are no artifacts, unless they are `required` and go to a temporary dir.
+    pub fn file_prefix(&self, required: bool) -> Option<String> {
+        let dir = match &self.work_dir {
+            Some(dir) => PathBuf::from(dir),
+            None if required => env::temp_dir().join(format!("pipeline-{}", process::id())),
+            None => return None,
+        };
+        if let Err(e) = fs::create_dir_all(&dir) {
+            println!("Something went wrong creating dirs: \n{e}");
+            exit(1)
+        }
+        let dir = fs::canonicalize(&dir).unwrap();
+        Some(format!("{}/{}.", dir.display(), self.name))
+    }
+
+    /// Writes the instance to the work dir as `{name}.vrp`.
+    pub fn write_instance(&mut self) -> String {
+        let path = self.serialize();
+        self.source = Some(path.clone());
+        path
+    }
+
+    /// Instance file for solvers that read one, it is written if the problem has none yet.
+    pub fn instance_file(&self) -> String {
+        match &self.source {
+            Some(source) => source.clone(),
+            None => self.serialize(),
+        }
+    }
+
+    fn serialize(&self) -> String {
+        let path = match &self.work_dir {
+            Some(dir) => {
+                if let Err(e) = fs::create_dir_all(dir) {
+                    println!("Something went wrong creating dirs: \n{e}");
+                    exit(1)
+                }
+                format!("{}/{}.vrp", dir, self.name)
+            }
+            None => format!("{}vrp", self.file_prefix(true).unwrap()),
+        };
+        match TspSerializer::serialize_file(&self.tsp, path) {
+            Ok((_, path)) => path,
+            Err(err) => {
+                println!("{}", err);
+                exit(1)
+            }
+        }
+    }
+
+    /// Writes the tours of `solution` to `{name}.sol` in the work dir, if there is one.
+    pub fn write_solution(&self, solution: &SolvingOutput) {
+        let Some(work_dir) = &self.work_dir else {
+            return;
+        };
+        let mut file = match fs::File::create(format!("{}/{}.sol", work_dir, self.name)) {
+            Ok(file) => file,
+            Err(e) => {
+                println!("Problem opening solution file {e}");
+                exit(1)
+            }
+        };
+
+        println!(
+            "{}writing tours to file {}/{}.sol",
+            log::cluster_prefix(),
+            work_dir,
+            self.name
+        );
+        (&self.tsp, solution).write_tours(&mut file).unwrap();
+    }
+}
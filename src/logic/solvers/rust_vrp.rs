@@ -1,33 +1,98 @@
-use super::{SolvingOutput, SolvingTrait};
-use std::fs::File;
-use std::io::BufReader;
+use super::{Problem, SolvingOutput, SolvingTrait};
+use crate::logic::util::log;
+use std::fmt::Write;
 use std::process::exit;
 use std::sync::Arc;
 
+use tspf::{Tsp, WeightKind};
 use vrp_scientific::{
     core::{
-        rosomaxa::prelude::TelemetryMode,
-        solver::{create_default_config_builder, Solver},
+        models::common::IdDimension,
+        rosomaxa::prelude::{InfoLogger, TelemetryMode},
+        solver::{
+            create_default_config_builder,
+            search::{
+                Recreate, RecreateWithCheapest, RecreateWithFarthest, RecreateWithNearestNeighbor,
+                RecreateWithRegret,
+            },
+            RecreateInitialOperator, Solver,
+        },
         utils::Environment,
     },
     tsplib::*,
 };
 
-pub struct RustVrpSolver;
+/// Recreate method used to build the initial solutions.
+pub enum InitialHeuristic {
+    /// The mix of initial operators vrp-core uses by default
+    Default,
+    Cheapest,
+    Farthest,
+    Regret,
+    NearestNeighbor,
+}
+
+pub struct RustVrpSolver {
+    pub max_generations: Option<usize>,
+    /// Time limit in seconds
+    pub time_limit: Option<usize>,
+    pub initial_heuristic: InitialHeuristic,
+    /// Print the solver telemetry to the progress output
+    pub telemetry: bool,
+}
+
+/// Writes the problem in the strict TSPLIB layout the vrp-scientific reader expects.
+///
+/// The reader rounds coordinates to integers.
+fn to_tsplib_string(vrp: &Tsp) -> String {
+    let mut ids = vrp.node_coords().keys().copied().collect::<Vec<usize>>();
+    ids.sort();
+
+    let mut tsplib = String::new();
+    writeln!(tsplib, "NAME : {}", vrp.name()).unwrap();
+    writeln!(tsplib, "COMMENT : {}", vrp.comment()).unwrap();
+    writeln!(tsplib, "TYPE : CVRP").unwrap();
+    writeln!(tsplib, "DIMENSION : {}", ids.len()).unwrap();
+    writeln!(tsplib, "EDGE_WEIGHT_TYPE : EUC_2D").unwrap();
+    writeln!(tsplib, "CAPACITY : {}", vrp.capacity()).unwrap();
+    writeln!(tsplib, "NODE_COORD_SECTION").unwrap();
+    for id in &ids {
+        let pos = vrp.node_coords().get(id).unwrap().pos();
+        writeln!(tsplib, "{} {} {}", id, pos[0], pos[1]).unwrap();
+    }
+    writeln!(tsplib, "DEMAND_SECTION").unwrap();
+    for id in &ids {
+        writeln!(tsplib, "{} {}", id, vrp.demands().get(id).unwrap_or(&0f64)).unwrap();
+    }
+    writeln!(tsplib, "DEPOT_SECTION").unwrap();
+    writeln!(tsplib, "{}", vrp.depots().iter().min().unwrap()).unwrap();
+    writeln!(tsplib, "-1").unwrap();
+    writeln!(tsplib, "EOF").unwrap();
+    tsplib
+}
 
 impl SolvingTrait for RustVrpSolver {
-    fn solve(&self, path: &str, _transform_only: Option<bool>) -> SolvingOutput {
-        let vrp_file = match File::open(path) {
-            Ok(file) => file,
-            Err(e) => {
-                println!("Something opening the temporary path of a vrp: \n{e}");
+    fn solve(&self, problem: &Problem, _transform_only: Option<bool>) -> SolvingOutput {
+        let vrp = &problem.tsp;
+        let depot = match vrp.depots().iter().min() {
+            Some(depot) => *depot,
+            None => {
+                println!("The sub VRP {} has no depot", problem.label());
                 exit(1)
             }
         };
-        let reader = BufReader::new(vrp_file);
+        // the vrp-scientific reader only computes euclidean distances
+        if vrp.weight_kind() != WeightKind::Euc2d {
+            println!(
+                "Rust VRP Solver: {} has edge weight type {:?}, only EUC_2D is supported",
+                problem.label(),
+                vrp.weight_kind()
+            );
+            exit(1)
+        }
 
         let arc_problem = {
-            let problem = match reader.read_tsplib(false) {
+            let problem = match to_tsplib_string(vrp).read_tsplib(false) {
                 Ok(problem) => problem,
                 Err(error) => {
                     println!("Something went wrong parsing a sub VRP: \n{error}");
@@ -37,20 +102,58 @@ impl SolvingTrait for RustVrpSolver {
             Arc::new(problem)
         };
 
-        let arc_env = Arc::new(Environment::default());
+        // the solver logs from its own threads, so the cluster is resolved here
+        let prefix = log::cluster_prefix();
+        let logger: InfoLogger =
+            Arc::new(move |msg: &str| println!("{prefix}Rust VRP Solver: {msg}"));
+        let telemetry_mode = if self.telemetry {
+            TelemetryMode::OnlyLogging {
+                logger: logger.clone(),
+                log_best: 100,
+                log_population: 1000,
+                dump_population: false,
+            }
+        } else {
+            TelemetryMode::None
+        };
+
+        let arc_env = Arc::new(Environment {
+            logger,
+            ..Environment::new_with_time_quota(self.time_limit)
+        });
 
-        let config =
-            match create_default_config_builder(arc_problem.clone(), arc_env, TelemetryMode::None)
-                .build()
-            {
-                Ok(config) => config,
-                Err(e) => {
-                    println!("Something went wrong building the config: \n{e}");
-                    exit(1)
-                }
-            };
+        let mut builder =
+            create_default_config_builder(arc_problem.clone(), arc_env.clone(), telemetry_mode)
+                .with_max_generations(self.max_generations)
+                .with_max_time(self.time_limit);
+
+        let random = arc_env.random.clone();
+        let recreate: Option<Arc<dyn Recreate + Send + Sync>> = match self.initial_heuristic {
+            InitialHeuristic::Default => None,
+            InitialHeuristic::Cheapest => Some(Arc::new(RecreateWithCheapest::new(random))),
+            InitialHeuristic::Farthest => Some(Arc::new(RecreateWithFarthest::new(random))),
+            InitialHeuristic::Regret => Some(Arc::new(RecreateWithRegret::new(2, 3, random))),
+            InitialHeuristic::NearestNeighbor => {
+                Some(Arc::new(RecreateWithNearestNeighbor::new(random)))
+            }
+        };
+        if let Some(recreate) = recreate {
+            builder = builder.with_initial(
+                4,
+                0.05,
+                vec![(Box::new(RecreateInitialOperator::new(recreate)), 1)],
+            );
+        }
+
+        let config = match builder.build() {
+            Ok(config) => config,
+            Err(e) => {
+                println!("Something went wrong building the config: \n{e}");
+                exit(1)
+            }
+        };
 
-        let solver = Solver::new(arc_problem.clone(), config);
+        let solver = Solver::new(arc_problem, config);
         let (solution, _cost) = match solver.solve() {
             Ok((sol, cost, _)) => (sol, cost),
             Err(e) => {
@@ -59,11 +162,20 @@ impl SolvingTrait for RustVrpSolver {
             }
         };
 
+        // the tsplib reader names every job after its node id minus one
         SolvingOutput::new(
             solution
                 .routes
                 .iter()
-                .map(|r| r.tour.all_activities().map(|a| a.place.location).collect())
+                .map(|r| {
+                    let customers = r.tour.all_activities().filter_map(|a| {
+                        a.job
+                            .as_ref()
+                            .and_then(|job| job.dimens.get_id())
+                            .map(|id| id.parse::<usize>().unwrap() + 1)
+                    });
+                    std::iter::once(depot).chain(customers).collect()
+                })
                 .collect(),
         )
     }
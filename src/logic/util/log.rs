@@ -0,0 +1,21 @@
+use std::cell::RefCell;
+
+thread_local! {
+    static CLUSTER: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Runs `f` with the output of the current thread attributed to `cluster`.
+pub fn with_cluster<T>(cluster: &str, f: impl FnOnce() -> T) -> T {
+    let outer = CLUSTER.with(|label| label.replace(Some(cluster.to_string())));
+    let result = f();
+    CLUSTER.with(|label| *label.borrow_mut() = outer);
+    result
+}
+
+/// `[cluster] ` if the thread solves a cluster in parallel to others, otherwise empty.
+pub fn cluster_prefix() -> String {
+    CLUSTER.with(|label| match &*label.borrow() {
+        Some(cluster) => format!("[{cluster}] "),
+        None => String::new(),
+    })
+}
@@ -1 +1,4 @@
+pub mod log;
+pub mod split;
+pub mod tour;
 pub mod tsp;
@@ -0,0 +1,113 @@
+use tspf::Tsp;
+
+use super::tsp::Distancing;
+
+/// Prins' Split procedure.
+///
+/// Cuts a giant tour into capacity feasible routes with minimal total length by
+/// computing a shortest path on the auxiliary graph of all feasible route segments.
+/// The giant tour must only contain customers. Every returned route starts with the depot.
+pub fn split(problem: &Tsp, depot: usize, sequence: &[usize]) -> Vec<Vec<usize>> {
+    let n = sequence.len();
+    let demand = |id: usize| *problem.demands().get(&id).unwrap_or(&0f64);
+    let distance = |a: usize, b: usize| problem.distance(a, b).unwrap();
+
+    // shortest path labels and predecessors over the positions 0..=n of the giant tour
+    let mut labels = vec![f64::INFINITY; n + 1];
+    let mut predecessors = vec![0usize; n + 1];
+    labels[0] = 0f64;
+
+    for i in 1..=n {
+        let mut load = 0f64;
+        let mut cost = 0f64;
+        for j in i..=n {
+            let customer = sequence[j - 1];
+            load += demand(customer);
+            if load > problem.capacity() && j > i {
+                break;
+            }
+            if j == i {
+                cost = distance(depot, customer) + distance(customer, depot);
+            } else {
+                let previous = sequence[j - 2];
+                cost += distance(previous, customer) + distance(customer, depot)
+                    - distance(previous, depot);
+            }
+            if labels[i - 1] + cost < labels[j] {
+                labels[j] = labels[i - 1] + cost;
+                predecessors[j] = i - 1;
+            }
+        }
+    }
+
+    let mut routes = vec![];
+    let mut j = n;
+    while j > 0 {
+        let i = predecessors[j];
+        let mut route = vec![depot];
+        route.extend_from_slice(&sequence[i..j]);
+        routes.push(route);
+        j = i;
+    }
+    routes.reverse();
+    routes
+}
+
+#[cfg(test)]
+mod tests {
+    use tspf::TspBuilder;
+
+    use super::*;
+
+    /// Depot 1 at the origin, customers 2 and 3 above it and 4 and 5 to its right.
+    fn problem(capacity: usize) -> Tsp {
+        TspBuilder::parse_str(format!(
+            "NAME: split
+TYPE: CVRP
+DIMENSION: 5
+CAPACITY: {capacity}
+EDGE_WEIGHT_TYPE: EUC_2D
+NODE_COORD_SECTION:
+1 0 0
+2 0 3
+3 0 4
+4 4 0
+5 5 0
+DEMAND_SECTION:
+1 0
+2 1
+3 1
+4 1
+5 1
+DEPOT_SECTION:
+1
+-1
+EOF
+"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn keeps_a_feasible_giant_tour() {
+        // 3 + 1 + sqrt(32) + 1 + 5 beats splitting it into two routes of length 8 and 10
+        assert_eq!(
+            split(&problem(4), 1, &[2, 3, 4, 5]),
+            vec![vec![1, 2, 3, 4, 5]]
+        );
+    }
+
+    #[test]
+    fn splits_at_the_capacity() {
+        // 8 + 10 beats 6 + (4 + sqrt(32) + 4) + 10 and 8 + 8 + 10
+        assert_eq!(
+            split(&problem(2), 1, &[2, 3, 4, 5]),
+            vec![vec![1, 2, 3], vec![1, 4, 5]]
+        );
+    }
+
+    #[test]
+    fn gives_oversized_customers_their_own_route() {
+        assert_eq!(split(&problem(0), 1, &[2, 3]), vec![vec![1, 2], vec![1, 3]]);
+    }
+}
@@ -0,0 +1,385 @@
+use std::collections::VecDeque;
+
+use tspf::Tsp;
+
+use super::tsp::Distancing;
+
+/// Dense distance matrix over the nodes of a problem.
+///
+/// Index `i` of the matrix belongs to node `ids[i]`, the ids are sorted ascending.
+pub struct DistanceMatrix {
+    pub ids: Vec<usize>,
+    pub distances: Vec<Vec<f64>>,
+}
+
+impl From<&Tsp> for DistanceMatrix {
+    fn from(tsp: &Tsp) -> Self {
+        let mut ids = tsp.node_coords().keys().copied().collect::<Vec<usize>>();
+        ids.sort();
+        let distances = ids
+            .iter()
+            .map(|a| ids.iter().map(|b| tsp.distance(*a, *b).unwrap()).collect())
+            .collect();
+        DistanceMatrix { ids, distances }
+    }
+}
+
+impl DistanceMatrix {
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn get(&self, a: usize, b: usize) -> f64 {
+        self.distances[a][b]
+    }
+
+    /// Length of the closed tour over matrix indices.
+    pub fn tour_length(&self, tour: &[usize]) -> f64 {
+        if tour.is_empty() {
+            return 0f64;
+        }
+        tour.windows(2).map(|w| self.get(w[0], w[1])).sum::<f64>()
+            + self.get(tour[tour.len() - 1], tour[0])
+    }
+
+    /// The `count` nearest other nodes for every node.
+    pub fn neighbours(&self, count: usize) -> Vec<Vec<usize>> {
+        (0..self.len())
+            .map(|a| {
+                let mut others = (0..self.len()).filter(|b| *b != a).collect::<Vec<usize>>();
+                others.sort_by(|b, c| self.get(a, *b).total_cmp(&self.get(a, *c)));
+                others.truncate(count);
+                others
+            })
+            .collect()
+    }
+
+    /// Nearest neighbour tour starting at `start`.
+    pub fn nearest_neighbour_tour(&self, start: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.len()];
+        let mut tour = Vec::with_capacity(self.len());
+        let mut current = start;
+        visited[current] = true;
+        tour.push(current);
+        while tour.len() < self.len() {
+            let next = (0..self.len())
+                .filter(|b| !visited[*b])
+                .min_by(|a, b| self.get(current, *a).total_cmp(&self.get(current, *b)))
+                .unwrap();
+            visited[next] = true;
+            tour.push(next);
+            current = next;
+        }
+        tour
+    }
+
+    /// Greedy edge matching: adds the shortest edges that keep every degree at most two
+    /// and close no subtour until a single tour remains.
+    pub fn greedy_tour(&self) -> Vec<usize> {
+        let n = self.len();
+        if n < 3 {
+            return (0..n).collect();
+        }
+        let mut edges = (0..n)
+            .flat_map(|a| (a + 1..n).map(move |b| (a, b)))
+            .collect::<Vec<(usize, usize)>>();
+        edges.sort_by(|(a, b), (c, d)| self.get(*a, *b).total_cmp(&self.get(*c, *d)));
+
+        // union find to avoid closing subtours
+        let mut parent = (0..n).collect::<Vec<usize>>();
+        fn find(parent: &mut [usize], a: usize) -> usize {
+            let mut root = a;
+            while parent[root] != root {
+                root = parent[root];
+            }
+            parent[a] = root;
+            root
+        }
+
+        let mut adjacent: Vec<Vec<usize>> = vec![vec![]; n];
+        let mut added = 0;
+        for (a, b) in edges {
+            if added == n - 1 {
+                break;
+            }
+            if adjacent[a].len() >= 2 || adjacent[b].len() >= 2 {
+                continue;
+            }
+            let (root_a, root_b) = (find(&mut parent, a), find(&mut parent, b));
+            if root_a == root_b {
+                continue;
+            }
+            parent[root_a] = root_b;
+            adjacent[a].push(b);
+            adjacent[b].push(a);
+            added += 1;
+        }
+
+        // walk the resulting hamiltonian path from one of its ends
+        let start = (0..n).find(|a| adjacent[*a].len() < 2).unwrap();
+        let mut tour = vec![start];
+        let mut previous = usize::MAX;
+        let mut current = start;
+        while let Some(next) = adjacent[current]
+            .iter()
+            .find(|next| **next != previous)
+            .copied()
+        {
+            tour.push(next);
+            previous = current;
+            current = next;
+        }
+        tour
+    }
+
+    /// Improves a tour with 2-opt and Or-opt moves until no improving move is left.
+    ///
+    /// Or-opt moves segments of up to three nodes, also inserting them reversed.
+    /// Candidate moves are restricted to the neighbour lists and nodes whose
+    /// surroundings didn't change are skipped with don't-look bits.
+    pub fn improve_tour(&self, tour: &mut Vec<usize>, neighbours: &[Vec<usize>]) {
+        let n = tour.len();
+        if n < 4 {
+            return;
+        }
+        let mut position = vec![0usize; self.len()];
+        for (i, node) in tour.iter().enumerate() {
+            position[*node] = i;
+        }
+
+        let mut queue: VecDeque<usize> = tour.iter().copied().collect();
+        let mut active = vec![false; self.len()];
+        for node in tour.iter() {
+            active[*node] = true;
+        }
+
+        while let Some(a) = queue.pop_front() {
+            active[a] = false;
+            let touched = self
+                .two_opt_move(tour, &mut position, neighbours, a)
+                .or_else(|| self.or_opt_move(tour, &mut position, neighbours, a));
+
+            if let Some(touched) = touched {
+                for node in touched.into_iter().chain(std::iter::once(a)) {
+                    if !active[node] {
+                        active[node] = true;
+                        queue.push_back(node);
+                    }
+                }
+            }
+        }
+    }
+
+    fn two_opt_move(
+        &self,
+        tour: &mut [usize],
+        position: &mut [usize],
+        neighbours: &[Vec<usize>],
+        a: usize,
+    ) -> Option<Vec<usize>> {
+        let n = tour.len();
+        let succ = |p: &[usize], node: usize| tour[(p[node] + 1) % n];
+        let pred = |p: &[usize], node: usize| tour[(p[node] + n - 1) % n];
+
+        for forward in [true, false] {
+            let b = if forward {
+                succ(position, a)
+            } else {
+                pred(position, a)
+            };
+            let removed = self.get(a, b);
+            for c in &neighbours[a] {
+                let c = *c;
+                let added = self.get(a, c);
+                if added >= removed {
+                    break;
+                }
+                let d = if forward {
+                    succ(position, c)
+                } else {
+                    pred(position, c)
+                };
+                if c == b || d == a {
+                    continue;
+                }
+                let gain = removed + self.get(c, d) - added - self.get(b, d);
+                if gain > 1e-9 {
+                    // replace (a, b), (c, d) with (a, c), (b, d)
+                    let (from, to) = if forward {
+                        (position[b], position[c])
+                    } else {
+                        (position[c], position[b])
+                    };
+                    reverse(tour, position, from, to);
+                    return Some(vec![b, c, d]);
+                }
+            }
+        }
+        None
+    }
+
+    fn or_opt_move(
+        &self,
+        tour: &mut Vec<usize>,
+        position: &mut [usize],
+        neighbours: &[Vec<usize>],
+        a: usize,
+    ) -> Option<Vec<usize>> {
+        let n = tour.len();
+        for length in 1..=3usize.min(n - 3) {
+            let start = position[a];
+            let segment = (0..length)
+                .map(|k| tour[(start + k) % n])
+                .collect::<Vec<usize>>();
+            let first = segment[0];
+            let last = segment[length - 1];
+            let previous = tour[(start + n - 1) % n];
+            let next = tour[(start + length) % n];
+            let removal_gain =
+                self.get(previous, first) + self.get(last, next) - self.get(previous, next);
+
+            for c in neighbours[first].iter().chain(neighbours[last].iter()) {
+                let c = *c;
+                if segment.contains(&c) {
+                    continue;
+                }
+                let d = tour[(position[c] + 1) % n];
+                if segment.contains(&d) {
+                    continue;
+                }
+                let base = self.get(c, d);
+                let forward_cost = self.get(c, first) + self.get(last, d) - base;
+                let reversed_cost = self.get(c, last) + self.get(first, d) - base;
+                let (cost, reversed) = if forward_cost <= reversed_cost {
+                    (forward_cost, false)
+                } else {
+                    (reversed_cost, true)
+                };
+                if removal_gain - cost > 1e-9 {
+                    let mut moved = segment.clone();
+                    if reversed {
+                        moved.reverse();
+                    }
+                    let mut rest = tour
+                        .iter()
+                        .filter(|node| !segment.contains(node))
+                        .copied()
+                        .collect::<Vec<usize>>();
+                    let insert_at = rest.iter().position(|node| *node == c).unwrap() + 1;
+                    rest.splice(insert_at..insert_at, moved);
+                    *tour = rest;
+                    for (i, node) in tour.iter().enumerate() {
+                        position[*node] = i;
+                    }
+                    return Some(vec![previous, next, c, d, first, last]);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Reverses the tour between the positions `from` and `to` (inclusive), wrapping around.
+fn reverse(tour: &mut [usize], position: &mut [usize], from: usize, to: usize) {
+    let n = tour.len();
+    let mut length = (to + n - from) % n + 1;
+    let (mut i, mut j) = (from, to);
+    while length > 1 {
+        tour.swap(i, j);
+        position[tour[i]] = i;
+        position[tour[j]] = j;
+        i = (i + 1) % n;
+        j = (j + n - 1) % n;
+        length -= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+    use super::*;
+
+    fn matrix(points: &[(f64, f64)]) -> DistanceMatrix {
+        DistanceMatrix {
+            ids: (1..=points.len()).collect(),
+            distances: points
+                .iter()
+                .map(|(ax, ay)| {
+                    points
+                        .iter()
+                        .map(|(bx, by)| ((ax - bx).powi(2) + (ay - by).powi(2)).sqrt())
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    fn positions(tour: &[usize], len: usize) -> Vec<usize> {
+        let mut position = vec![0; len];
+        for (i, node) in tour.iter().enumerate() {
+            position[*node] = i;
+        }
+        position
+    }
+
+    #[test]
+    fn two_opt_removes_a_crossing() {
+        // the corners of the unit square visited crosswise, 2 + 2 * sqrt(2) instead of 4
+        let matrix = matrix(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+        let mut tour = vec![0, 2, 1, 3];
+        let mut position = positions(&tour, 4);
+        let neighbours = matrix.neighbours(3);
+
+        assert!(matrix
+            .two_opt_move(&mut tour, &mut position, &neighbours, 0)
+            .is_some());
+        assert!((matrix.tour_length(&tour) - 4.0).abs() < 1e-9);
+        assert_eq!(position, positions(&tour, 4));
+    }
+
+    #[test]
+    fn or_opt_moves_a_segment() {
+        // nodes on a line, moving 1 between 0 and 2 saves 2 + 3 - 1 and costs 1 + 1 - 2
+        let matrix = matrix(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0), (4.0, 0.0)]);
+        let mut tour = vec![0, 2, 3, 1, 4];
+        let mut position = positions(&tour, 5);
+        let neighbours = matrix.neighbours(4);
+        assert_eq!(matrix.tour_length(&tour), 12.0);
+
+        assert!(matrix
+            .or_opt_move(&mut tour, &mut position, &neighbours, 1)
+            .is_some());
+        assert_eq!(tour, vec![0, 1, 2, 3, 4]);
+        assert_eq!(matrix.tour_length(&tour), 8.0);
+        assert_eq!(position, positions(&tour, 5));
+    }
+
+    #[test]
+    fn improve_tour_never_increases_the_length() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for n in [4, 5, 8, 20, 50] {
+            let points = (0..n)
+                .map(|_| (rng.gen_range(0.0..100.0), rng.gen_range(0.0..100.0)))
+                .collect::<Vec<(f64, f64)>>();
+            let matrix = matrix(&points);
+            for count in [3, n - 1] {
+                let neighbours = matrix.neighbours(count);
+                let mut tour = (0..n).collect::<Vec<usize>>();
+                tour.shuffle(&mut rng);
+                let before = matrix.tour_length(&tour);
+
+                matrix.improve_tour(&mut tour, &neighbours);
+
+                assert!(matrix.tour_length(&tour) <= before + 1e-9);
+                let mut nodes = tour.clone();
+                nodes.sort();
+                assert_eq!(nodes, (0..n).collect::<Vec<usize>>());
+            }
+        }
+    }
+}
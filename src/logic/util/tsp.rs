@@ -32,7 +32,7 @@ impl Distancing<f64> for Tsp {
     }
 }
 
-pub fn calculate_solution_score(problem: &Tsp, paths: &Vec<Vec<usize>>) -> f64 {
+pub fn calculate_solution_score(problem: &Tsp, paths: &[Vec<usize>]) -> f64 {
     paths
         .iter()
         .map(|path| {
@@ -1,88 +1,397 @@
 mod args;
+mod config;
 mod error_code;
 mod logic;
 
 use std::env;
 use std::path::Path;
 use std::process::exit;
+use std::sync::Arc;
 
 use args::{
-    ClusterOption, OnlySolveCommand, SolveCommand, SolverOption, VRPCommand, VRPSolverArgs,
+    BetaScheduleOption, ClusterArgs, ClusterOption, InitialHeuristicOption, PipelineOption,
+    QuboEnergyCommand, QuboExportFormatOption, QuboFormulationOption, SolveCommand, SolverArgs,
+    SolverOption, TourConstructionOption, VRPCommand, VRPSolverArgs, VerifyQuboCommand,
 };
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
+use config::{apply_config, effective_config_path, required, write_effective_config};
+use serde_json::Value;
 
-use logic::clustering::{ClusterTspClustering, KMeansClustering};
+use logic::clustering::{
+    CapacitatedKMeansClustering, ClusterTspClustering, ClusteringTrait, FileClustering,
+    GeneralizedAssignmentClustering, KMeansClustering, NoClustering, SweepClustering,
+};
+use logic::hierarchical::HierarchicalSolver;
+use logic::qubo::{
+    max_tsp_nodes, verify_tsp_qubo, BetaSchedule, DecomposingSampler, ExhaustiveSampler,
+    ParallelTemperingSampler, QaoaSampler, QuboExportFormat, QuboFormulation, QuboParameters,
+    QuboSampler, SampleSet, SimulatedAnnealingSampler, TabuSampler, TspQuboBuilder,
+    MAX_ENUMERATED_VARIABLES,
+};
+use logic::route_first::RouteFirstSolver;
 use logic::solver::VrpSolver;
-use logic::solvers::{DummySolver, FileSolver, HybridTspSolver, LKHSolver, SolvingTrait};
+use logic::solvers::{
+    DummySolver, ExternalSolver, ExternalSolvers, FileSolver, HeldKarpSolver, HybridTspSolver,
+    HybridTspSolverType, InitialHeuristic, LKHSolver, LocalSearchSolver, Problem, RustVrpSolver,
+    SolvingTrait, TourConstruction,
+};
 use tspf::{TspBuilder, TspKind};
 
-use crate::logic::solvers::VRPTourWriter;
+impl From<&InitialHeuristicOption> for InitialHeuristic {
+    fn from(option: &InitialHeuristicOption) -> Self {
+        match option {
+            InitialHeuristicOption::Default => InitialHeuristic::Default,
+            InitialHeuristicOption::Cheapest => InitialHeuristic::Cheapest,
+            InitialHeuristicOption::Farthest => InitialHeuristic::Farthest,
+            InitialHeuristicOption::Regret => InitialHeuristic::Regret,
+            InitialHeuristicOption::NearestNeighbor => InitialHeuristic::NearestNeighbor,
+        }
+    }
+}
 
-impl From<&SolveCommand> for Box<dyn SolvingTrait> {
-    fn from(options: &SolveCommand) -> Self {
-        match options.solver {
-            SolverOption::Lkh => Box::new(LKHSolver {
-                binary: String::from("./bin/LKH"),
-                lkh_solution: options.lkh_solution.clone(),
-            }),
-            SolverOption::Simulated => Box::new(HybridTspSolver {
-                quantum_type: logic::solvers::HybridTspSolverType::Simulated,
-                qubo_solution: options.qubo_solution.clone(),
-            }),
-            SolverOption::LeapHybrid => Box::new(HybridTspSolver {
-                quantum_type: logic::solvers::HybridTspSolverType::LeapHybrid,
-                qubo_solution: options.qubo_solution.clone(),
-            }),
-            SolverOption::QbSolv => Box::new(HybridTspSolver {
-                quantum_type: logic::solvers::HybridTspSolverType::QbSolv,
-                qubo_solution: options.qubo_solution.clone(),
-            }),
-            SolverOption::Direct => Box::new(HybridTspSolver {
-                quantum_type: logic::solvers::HybridTspSolverType::Direct,
-                qubo_solution: options.qubo_solution.clone(),
-            }),
-            SolverOption::SolutionFromFile => Box::new(FileSolver {
-                solution_file_dir: options.solution_dir.clone(),
-            }),
+impl From<&QuboFormulationOption> for QuboFormulation {
+    fn from(option: &QuboFormulationOption) -> Self {
+        match option {
+            QuboFormulationOption::Full => QuboFormulation::Full,
+            QuboFormulationOption::DepotFixed => QuboFormulation::DepotFixed,
         }
     }
 }
 
-impl From<&OnlySolveCommand> for Box<dyn SolvingTrait> {
-    fn from(options: &OnlySolveCommand) -> Self {
-        match options.solver {
-            SolverOption::Lkh => Box::new(LKHSolver {
-                binary: String::from("./bin/LKH"),
-                lkh_solution: options.lkh_solution.clone(),
-            }),
-            SolverOption::Simulated => Box::new(HybridTspSolver {
-                quantum_type: logic::solvers::HybridTspSolverType::Simulated,
-                qubo_solution: options.qubo_solution.clone(),
-            }),
-            SolverOption::LeapHybrid => Box::new(HybridTspSolver {
-                quantum_type: logic::solvers::HybridTspSolverType::LeapHybrid,
-                qubo_solution: options.qubo_solution.clone(),
-            }),
-            SolverOption::QbSolv => Box::new(HybridTspSolver {
-                quantum_type: logic::solvers::HybridTspSolverType::QbSolv,
-                qubo_solution: options.qubo_solution.clone(),
-            }),
-            SolverOption::Direct => Box::new(HybridTspSolver {
-                quantum_type: logic::solvers::HybridTspSolverType::Direct,
-                qubo_solution: options.qubo_solution.clone(),
+impl From<&BetaScheduleOption> for BetaSchedule {
+    fn from(option: &BetaScheduleOption) -> Self {
+        match option {
+            BetaScheduleOption::Linear => BetaSchedule::Linear,
+            BetaScheduleOption::Geometric => BetaSchedule::Geometric,
+        }
+    }
+}
+
+impl From<&QuboExportFormatOption> for QuboExportFormat {
+    fn from(option: &QuboExportFormatOption) -> Self {
+        match option {
+            QuboExportFormatOption::Coordinate => QuboExportFormat::Coordinate,
+            QuboExportFormatOption::DwaveLp => QuboExportFormat::DwaveLp,
+            QuboExportFormatOption::Lp => QuboExportFormat::Lp,
+            QuboExportFormatOption::Qbsolv => QuboExportFormat::Qbsolv,
+            QuboExportFormatOption::BqmJson => QuboExportFormat::BqmJson,
+            QuboExportFormatOption::Ising => QuboExportFormat::Ising,
+            QuboExportFormatOption::Pauli => QuboExportFormat::Pauli,
+        }
+    }
+}
+
+/// The `lkh` external solver running the given LKH-3 binary.
+fn lkh_solver(external: &ExternalSolvers, binary: &str) -> ExternalSolver {
+    // the wrapper runs in its own directory
+    let binary = match env::current_dir() {
+        Ok(dir) => dir.join(binary),
+        Err(_) => Path::new(binary).to_path_buf(),
+    };
+    external
+        .get("lkh")
+        .with_option("lkh_instance", Value::from(binary.to_str().unwrap()))
+}
+
+/// Build dir the subproblems are written to, none if they are kept in memory.
+fn artifact_dir(options: &SolveCommand) -> Option<String> {
+    (!options.in_memory).then(|| options.build_dir.clone())
+}
+
+/// Name given with `--external-solver`, which the external solvers require.
+fn external_solver_name(name: &Option<String>) -> &str {
+    match name {
+        Some(name) => name,
+        None => {
+            println!(
+                "The external solvers need the name of a solver, given with --external-solver"
+            );
+            exit(1)
+        }
+    }
+}
+
+/// Inverse temperatures of `--beta-start` and `--beta-end`, which are only given together,
+/// are positive and don't decrease.
+fn beta_range(options: &SolverArgs) -> Option<(f64, f64)> {
+    match (options.beta_start, options.beta_end) {
+        (Some(start), Some(end)) if start > 0f64 && end >= start => Some((start, end)),
+        (Some(start), Some(end)) => {
+            println!(
+                "beta_start {start} and beta_end {end} have to be positive with beta_end at least beta_start"
+            );
+            exit(1)
+        }
+        (None, None) => None,
+        _ => {
+            println!("beta_start and beta_end have to be given together");
+            exit(1)
+        }
+    }
+}
+
+/// Reads the QUBO weights from the config file and applies the flags on top.
+fn qubo_parameters(
+    config: &Option<String>,
+    penalty: Option<f64>,
+    objective_weight: Option<f64>,
+    auto_penalty: bool,
+    penalty_factor: Option<f64>,
+) -> QuboParameters {
+    let mut parameters = match config {
+        Some(path) => QuboParameters::from_file(path),
+        None => QuboParameters::default(),
+    };
+    if penalty.is_some() {
+        parameters.penalty = penalty;
+    }
+    if let Some(objective_weight) = objective_weight {
+        parameters.objective_weight = objective_weight;
+    }
+    if auto_penalty {
+        parameters.auto_penalty = true;
+    }
+    if let Some(penalty_factor) = penalty_factor {
+        parameters.penalty_factor = penalty_factor;
+    }
+    parameters
+}
+
+/// Replaces the QUBO config file by the weights it resolves to, so the effective
+/// config records the weights instead of a path to a file that may change.
+fn resolve_qubo_parameters(options: &mut SolverArgs) {
+    let parameters = qubo_parameters(
+        &options.qubo_config.take(),
+        options.qubo_penalty,
+        options.qubo_objective_weight,
+        options.qubo_auto_penalty,
+        options.qubo_penalty_factor,
+    );
+    options.qubo_penalty = parameters.penalty;
+    options.qubo_objective_weight = Some(parameters.objective_weight);
+    options.qubo_auto_penalty = parameters.auto_penalty;
+    options.qubo_penalty_factor = Some(parameters.penalty_factor);
+}
+
+/// Solver of the subproblems selected by the solver flags.
+fn solver(options: &SolverArgs) -> Box<dyn SolvingTrait> {
+    solver_of(required(&options.solver, "solver"), options)
+}
+
+/// Solver `solver` with the parameters of the solver flags.
+fn solver_of(solver: &SolverOption, options: &SolverArgs) -> Box<dyn SolvingTrait> {
+    let external = ExternalSolvers::load(&options.external_config, &options.python_dir);
+    let quantum = |quantum_type: HybridTspSolverType| -> Box<dyn QuboSampler> {
+        match quantum_type {
+            HybridTspSolverType::Qaoa => Box::new(QaoaSampler {
+                layers: options.qaoa_layers,
+                reads: options.reads,
+                iterations: options.qaoa_iterations,
+                max_qubits: options.qaoa_max_qubits,
+                seed: options.seed,
             }),
-            SolverOption::SolutionFromFile => Box::new(FileSolver {
-                solution_file_dir: options.solution_dir.clone(),
+            quantum_type => Box::new(
+                external
+                    .get("dwave")
+                    .with_option("type", Value::from(quantum_type.to_string())),
+            ),
+        }
+    };
+    let hybrid = |sampler: Box<dyn QuboSampler>| -> Box<dyn SolvingTrait> {
+        let sampler: Box<dyn QuboSampler> = match options.max_qubo_variables {
+            Some(max_variables) => Box::new(DecomposingSampler {
+                sub_sampler: sampler,
+                max_variables,
+                rounds: options.decomposition_rounds,
+                sweeps: options.sweeps,
+                seed: options.seed,
             }),
+            None => sampler,
+        };
+        Box::new(HybridTspSolver {
+            sampler,
+            qubo_solution: options.qubo_solution.clone(),
+            formulation: QuboFormulation::from(&options.qubo_formulation),
+            parameters: qubo_parameters(
+                &options.qubo_config,
+                options.qubo_penalty,
+                options.qubo_objective_weight,
+                options.qubo_auto_penalty,
+                options.qubo_penalty_factor,
+            ),
+            export_formats: options
+                .export_formats
+                .iter()
+                .map(QuboExportFormat::from)
+                .collect(),
+        })
+    };
+    match solver {
+        SolverOption::Lkh => Box::new(LKHSolver {
+            solver: lkh_solver(&external, &options.lkh_binary),
+            lkh_solution: options.lkh_solution.clone(),
+        }),
+        SolverOption::Simulated => hybrid(quantum(HybridTspSolverType::Simulated)),
+        SolverOption::LeapHybrid => hybrid(quantum(HybridTspSolverType::LeapHybrid)),
+        SolverOption::QbSolv => hybrid(quantum(HybridTspSolverType::QbSolv)),
+        SolverOption::Direct => hybrid(quantum(HybridTspSolverType::Direct)),
+        SolverOption::Annealing => hybrid(Box::new(SimulatedAnnealingSampler {
+            reads: options.reads,
+            sweeps: options.sweeps,
+            beta_range: beta_range(options),
+            schedule: BetaSchedule::from(&options.beta_schedule),
+            seed: options.seed,
+        })),
+        SolverOption::Exhaustive => hybrid(Box::new(ExhaustiveSampler {
+            max_variables: MAX_ENUMERATED_VARIABLES,
+        })),
+        SolverOption::Tabu => hybrid(Box::new(TabuSampler {
+            reads: options.reads,
+            sweeps: options.sweeps,
+            tenure: options.tabu_tenure,
+            seed: options.seed,
+        })),
+        SolverOption::Tempering => hybrid(Box::new(ParallelTemperingSampler {
+            reads: options.reads,
+            sweeps: options.sweeps,
+            replicas: options.replicas,
+            beta_range: beta_range(options),
+            seed: options.seed,
+        })),
+        SolverOption::Qaoa => hybrid(quantum(HybridTspSolverType::Qaoa)),
+        SolverOption::External => {
+            Box::new(external.get(external_solver_name(&options.external_solver)))
         }
+        SolverOption::ExternalQubo => hybrid(Box::new(
+            external.get(external_solver_name(&options.external_solver)),
+        )),
+        SolverOption::SolutionFromFile => Box::new(FileSolver {
+            solution_file_dir: options.solution_dir.clone(),
+        }),
+        SolverOption::RustVrp => Box::new(RustVrpSolver {
+            max_generations: options.max_generations,
+            time_limit: options.time_limit,
+            initial_heuristic: InitialHeuristic::from(&options.initial_heuristic),
+            telemetry: options.telemetry,
+        }),
+        SolverOption::LocalSearch => Box::new(LocalSearchSolver {
+            construction: match options.construction {
+                TourConstructionOption::NearestNeighbour => TourConstruction::NearestNeighbour,
+                TourConstructionOption::Greedy => TourConstruction::Greedy,
+            },
+            neighbours: options.neighbours,
+        }),
+        SolverOption::HeldKarp => Box::new(HeldKarpSolver {
+            max_nodes: options.max_exact_nodes,
+        }),
+    }
+}
+
+impl From<&SolveCommand> for Box<dyn ClusteringTrait> {
+    fn from(options: &SolveCommand) -> Self {
+        clustering(required(&options.cluster, "cluster"), &options.clustering)
+    }
+}
+
+/// Cluster-first pipeline that keeps clustering subproblems larger than the node
+/// limit with the strategies of the deeper levels.
+fn hierarchical_solver(options: &SolveCommand) -> Box<dyn SolvingTrait> {
+    let formulation = QuboFormulation::from(&options.solving.qubo_formulation);
+    let max_nodes = [
+        options.max_nodes,
+        options
+            .qubit_budget
+            .map(|qubits| max_tsp_nodes(qubits, formulation)),
+    ]
+    .into_iter()
+    .flatten()
+    .min();
+    let (max_nodes, max_depth, leaf_depth) =
+        match (max_nodes, required(&options.solving.solver, "solver")) {
+            (Some(max_nodes), _) => (max_nodes, options.max_depth, None),
+            // the direct solver always solves the routes of the clusters
+            (None, SolverOption::Direct) => (0, 2, Some(2)),
+            (None, _) => (usize::MAX, 1, None),
+        };
+
+    let mut levels: Vec<Arc<dyn ClusteringTrait>> =
+        vec![Arc::from(Box::<dyn ClusteringTrait>::from(options))];
+    levels.extend(
+        options
+            .level_clusters
+            .iter()
+            .map(|cluster| Arc::from(clustering(cluster, &options.clustering))),
+    );
+
+    let mut leaf_solvers: Vec<Arc<dyn SolvingTrait>> = vec![Arc::from(solver(&options.solving))];
+    leaf_solvers.extend(
+        options
+            .level_solvers
+            .iter()
+            .map(|level_solver| Arc::from(solver_of(level_solver, &options.solving))),
+    );
+
+    Box::new(HierarchicalSolver {
+        levels,
+        leaf_solvers,
+        max_nodes,
+        max_depth,
+        leaf_depth,
+        build_dir: artifact_dir(options),
+        workers: options.workers as usize,
+    })
+}
+
+/// Clustering strategy `cluster` with the given parameters.
+fn clustering(cluster: &ClusterOption, options: &ClusterArgs) -> Box<dyn ClusteringTrait> {
+    match cluster {
+        ClusterOption::Kmeans => Box::new(KMeansClustering {
+            count: options.cluster_number,
+        }),
+        ClusterOption::CapacitatedKmeans => Box::new(CapacitatedKMeansClustering {
+            max_iterations: options.kmeans_iterations,
+        }),
+        ClusterOption::Sweep => Box::new(SweepClustering {
+            start_angle: options.sweep_start_angle,
+            clockwise: options.sweep_clockwise,
+            tries: options.sweep_tries,
+        }),
+        ClusterOption::GeneralizedAssignment => Box::new(GeneralizedAssignmentClustering {
+            max_seconds: options.assignment_time_limit,
+        }),
+        ClusterOption::None => Box::new(NoClustering {}),
+        ClusterOption::Tsp => Box::new(ClusterTspClustering {}),
+        ClusterOption::ClusterFromFile => Box::new(FileClustering {
+            map_file_path: options.cluster_file.clone(),
+        }),
     }
 }
 
 fn main() {
-    let args = VRPSolverArgs::parse();
+    let matches = VRPSolverArgs::command().get_matches();
+    let args = match VRPSolverArgs::from_arg_matches(&matches) {
+        Ok(args) => args,
+        Err(e) => e.exit(),
+    };
 
     match args.command {
-        VRPCommand::Solve(subcommandargs) => {
+        VRPCommand::Solve(mut subcommandargs) => {
+            if let Some(config) = subcommandargs.config.clone() {
+                let matches = matches.subcommand_matches("solve").unwrap();
+                subcommandargs = Box::new(apply_config(*subcommandargs, matches, &config));
+            }
+            resolve_qubo_parameters(&mut subcommandargs.solving);
+            write_effective_config(
+                &subcommandargs,
+                &effective_config_path(&subcommandargs.solving.solution_dir, &subcommandargs.path),
+            );
+            if let Some(build_dir) = artifact_dir(&subcommandargs) {
+                write_effective_config(
+                    &subcommandargs,
+                    &format!("{build_dir}/pipeline.config.toml"),
+                );
+            }
+
             let path = subcommandargs.path.clone();
             match env::current_dir() {
                 Ok(path) => println!("working dir: {}", path.display()),
@@ -91,110 +400,184 @@ fn main() {
             println!("path: {}", path);
 
             println!("solve");
-            let solver = if let SolverOption::Direct = subcommandargs.solver {
-                VrpSolver {
-                    cluster_strat: match subcommandargs.cluster {
-                        ClusterOption::Kmeans => Box::new(KMeansClustering {
-                            count: subcommandargs.cluster_number,
-                        }),
-                        ClusterOption::Tsp => Box::new(ClusterTspClustering {}),
-                        ClusterOption::ClusterFromFile => {
-                            Box::new(logic::clustering::FileClustering {
-                                map_file_path: subcommandargs.cluster_file.clone(),
-                            })
-                        }
-                    },
-                    solving_strat: Box::new(VrpSolver {
-                        cluster_strat: Box::new(ClusterTspClustering {}),
-                        solving_strat: Box::<dyn SolvingTrait>::from(&subcommandargs),
-                        build_dir: Some(subcommandargs.build_dir.clone()),
-                    }),
-                    build_dir: Some(subcommandargs.build_dir),
-                }
-            } else {
-                VrpSolver {
-                    cluster_strat: match subcommandargs.cluster {
-                        ClusterOption::Kmeans => Box::new(KMeansClustering {
-                            count: subcommandargs.cluster_number,
-                        }),
-                        ClusterOption::Tsp => Box::new(ClusterTspClustering {}),
-                        ClusterOption::ClusterFromFile => {
-                            Box::new(logic::clustering::FileClustering {
-                                map_file_path: subcommandargs.cluster_file.clone(),
-                            })
-                        }
-                    },
-                    solving_strat: Box::<dyn SolvingTrait>::from(&subcommandargs),
-                    build_dir: Some(subcommandargs.build_dir),
-                }
+            let solver: Box<dyn SolvingTrait> = match subcommandargs.pipeline {
+                PipelineOption::RouteFirst => Box::new(RouteFirstSolver {
+                    solving_strat: solver(&subcommandargs.solving),
+                    build_dir: artifact_dir(&subcommandargs),
+                }),
+                PipelineOption::ClusterFirst => hierarchical_solver(subcommandargs.as_ref()),
             };
 
-            println!("result {:?}", solver.solve(&path[..], Option::None));
+            println!(
+                "result {:?}",
+                solver.solve(&Problem::read(&path), Option::None)
+            );
         }
         VRPCommand::Partial(partial) => match partial.subcommand {
             args::PartialSolveSubCommand::Cluster(cluster_opt) => {
                 let solver = VrpSolver {
-                    cluster_strat: match cluster_opt.cluster {
-                        ClusterOption::Kmeans => Box::new(KMeansClustering {
-                            count: cluster_opt.cluster_number,
-                        }),
-                        ClusterOption::Tsp => Box::new(ClusterTspClustering {}),
-                        ClusterOption::ClusterFromFile => {
-                            Box::new(logic::clustering::FileClustering {
-                                map_file_path: cluster_opt.cluster_file,
-                            })
-                        }
-                    },
+                    cluster_strat: clustering(&cluster_opt.cluster, &cluster_opt.clustering),
                     solving_strat: Box::new(DummySolver {}),
                     build_dir: Some(cluster_opt.build_dir),
+                    workers: 1,
                 };
 
-                let vrp = TspBuilder::parse_path(&cluster_opt.path[..]).unwrap();
+                let problem = Problem::read(&cluster_opt.path);
 
-                if vrp.kind() != TspKind::Cvrp {
+                if problem.tsp.kind() != TspKind::Cvrp {
                     panic!(
                         "Invalid TSPLIB instance type {}. (supported is CVRP)",
-                        vrp.kind().to_string().to_uppercase()
+                        problem.tsp.kind().to_string().to_uppercase()
                     );
                 }
 
-                solver.partial_cluster(&cluster_opt.path[..], &vrp);
+                solver.partial_cluster(&problem);
             }
-            args::PartialSolveSubCommand::Solve(solve_opt) => {
-                let solver = Box::<dyn SolvingTrait>::from(&solve_opt);
+            args::PartialSolveSubCommand::Solve(mut solve_opt) => {
+                if let Some(config) = solve_opt.config.clone() {
+                    let matches = matches
+                        .subcommand_matches("partial")
+                        .and_then(|matches| matches.subcommand_matches("solve"))
+                        .unwrap();
+                    solve_opt = Box::new(apply_config(*solve_opt, matches, &config));
+                }
+                resolve_qubo_parameters(&mut solve_opt.solving);
+                write_effective_config(
+                    &solve_opt,
+                    &effective_config_path(&solve_opt.solving.solution_dir, &solve_opt.path),
+                );
+                let solver = solver(&solve_opt.solving);
 
-                let path = &solve_opt.path[..];
+                let problem = Problem::read(&solve_opt.path);
 
-                let solution = solver.solve(path, Option::Some(solve_opt.transform_only));
+                let solution = solver.solve(&problem, Option::Some(solve_opt.transform_only));
 
                 if solve_opt.transform_only {
                     return;
                 }
 
-                let vrp = TspBuilder::parse_path(path).unwrap();
-
-                if vrp.kind() != TspKind::Cvrp {
+                if problem.tsp.kind() != TspKind::Cvrp {
                     panic!(
                         "Invalid TSPLIB instance type {}. (supported is CVRP)",
-                        vrp.kind().to_string().to_uppercase()
+                        problem.tsp.kind().to_string().to_uppercase()
                     );
                 }
 
-                let file_dir = Path::new(path).parent().unwrap().to_str().unwrap();
-                let file_name = Path::new(path).file_stem().unwrap().to_str().unwrap();
-
-                let mut file =
-                    match std::fs::File::create(format!("{}/{}.sol", file_dir, file_name)) {
-                        Ok(file) => file,
-                        Err(e) => {
-                            println!("Problem opening solution file {e}");
-                            exit(1)
-                        }
-                    };
-
-                println!("writing tours to file {file_dir}/{file_name}.sol");
-                (&vrp, &solution).write_tours(&mut file).unwrap();
+                problem.write_solution(&solution);
             }
         },
+        VRPCommand::VerifyQubo(verify_opt) => verify_qubo(&verify_opt),
+        VRPCommand::QuboEnergy(energy_opt) => qubo_energy(&energy_opt),
+    }
+}
+
+/// Checks the QUBO of a small TSP for every requested penalty and exits with an
+/// error if any of them doesn't encode the tour lengths.
+fn verify_qubo(options: &VerifyQuboCommand) {
+    let tsp = match TspBuilder::parse_path(&options.path) {
+        Ok(tsp) => tsp,
+        Err(e) => {
+            println!("problems with parsing {}: {e}", options.path);
+            exit(1)
+        }
+    };
+    let builder = TspQuboBuilder::new(&tsp, QuboFormulation::from(&options.qubo_formulation));
+    if builder.size() > 9 {
+        println!(
+            "{} cities are too many to enumerate all tours, at most 9 are supported",
+            builder.size()
+        );
+        exit(1)
+    }
+
+    let b = options.qubo_objective_weight;
+    let penalties = if options.qubo_penalty.is_empty() {
+        vec![builder.min_penalty(b), builder.default_penalty(b)]
+    } else {
+        options.qubo_penalty.clone()
+    };
+
+    let mut failed = false;
+    for a in penalties {
+        let verification = verify_tsp_qubo(&builder, a, b, options.max_variables);
+        let ground_states = match verification.ground_states {
+            Some(count) => format!("{count} ground states"),
+            None => String::from("ground states not enumerated"),
+        };
+        println!(
+            "penalty {a}, objective weight {b}: {} tours, optimal length {}, constant {}, {ground_states}",
+            verification.tours, verification.optimal_length, verification.constant
+        );
+        for mismatch in &verification.mismatches {
+            println!("  mismatch at penalty {a}, objective weight {b}: {mismatch}");
+        }
+        failed |= !verification.mismatches.is_empty();
+    }
+
+    if failed {
+        exit(1)
+    }
+}
+
+/// Prints the energy terms of every sample in the sample file.
+fn qubo_energy(options: &QuboEnergyCommand) {
+    let tsp = match TspBuilder::parse_path(&options.path) {
+        Ok(tsp) => tsp,
+        Err(e) => {
+            println!("problems with parsing {}: {e}", options.path);
+            exit(1)
+        }
+    };
+    let builder = TspQuboBuilder::new(&tsp, QuboFormulation::from(&options.qubo_formulation));
+    let parameters = qubo_parameters(
+        &options.qubo_config,
+        options.qubo_penalty,
+        options.qubo_objective_weight,
+        false,
+        None,
+    );
+    let b = parameters.objective_weight;
+    let a = parameters
+        .penalty
+        .unwrap_or_else(|| builder.default_penalty(b));
+
+    let content = match std::fs::read_to_string(&options.sample_file) {
+        Ok(content) => content,
+        Err(e) => {
+            println!("Problem opening file \"{}\": {e}", options.sample_file);
+            exit(1)
+        }
+    };
+    let sample_set = match SampleSet::parse(&content) {
+        Ok(sample_set) => sample_set,
+        Err(e) => {
+            println!(
+                "Problem parsing sample set \"{}\": {e}",
+                options.sample_file
+            );
+            exit(1)
+        }
+    };
+
+    println!("penalty {a}, objective weight {b}");
+    for (k, sample) in sample_set.samples.iter().enumerate() {
+        if sample.len() != builder.variables() {
+            println!(
+                "sample {k}: {} variables, the QUBO has {}",
+                sample.len(),
+                builder.variables()
+            );
+            continue;
+        }
+        let breakdown = builder.energy_breakdown(sample, a, b);
+        let (rows, columns) = builder.violations(sample);
+        let reported = match sample_set.energies.get(k) {
+            Some(energy) => format!(", reported energy {energy}"),
+            None => String::new(),
+        };
+        println!(
+            "sample {k}: {breakdown}{reported}, {rows} row and {columns} column violations, decoded tour {:?}",
+            builder.decode(sample).tour
+        );
     }
 }